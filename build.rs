@@ -4,4 +4,8 @@ fn main() {
     println!("cargo:rustc-env=TARGET_OS={}", target_os().unwrap());
     println!("cargo:rustc-env=TARGET_ENV={}", target_env().unwrap());
     println!("cargo:rustc-env=TARGET_ARCH={}", target_arch().unwrap());
+
+    // the key releases are verified with is compiled in, see `RELEASE_PUBLIC_KEY`
+    println!("cargo:rerun-if-env-changed=LIMIT_UP_RELEASE_KEY");
+    println!("cargo:rerun-if-changed=build.rs");
 }
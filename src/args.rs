@@ -1,5 +1,11 @@
+use std::path::PathBuf;
+
 pub use clap::{Parser, Subcommand};
 
+use r18::tr;
+
+use crate::core::{helper::is_env_name, release::Channel};
+
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
@@ -11,4 +17,290 @@ pub struct Args {
 pub enum Command {
     /// Open TUI
     Tui,
+    /// Install limit-server without TUI
+    Install(InstallArgs),
+    /// Update limit-server to the latest version
+    Update(UpdateArgs),
+    /// Uninstall limit-server
+    Uninstall(UninstallArgs),
+    /// Show what is installed and whether it is healthy
+    Status(StatusArgs),
+    /// Check whether limit-server can be installed, these checks also run before installing
+    Doctor(DoctorArgs),
+    /// Run limit-server as a service
+    Service(ServiceArgs),
+    /// Start limit-server
+    Start(ServerArgs),
+    /// Stop limit-server
+    Stop(ServerArgs),
+    /// Restart limit-server
+    Restart(ServerArgs),
+    /// Print logs of limit-server
+    Logs(LogsArgs),
+    /// Run limit-server under the supervisor of limit-up, this is what `start` runs
+    /// in the background if there is no service
+    #[command(hide = true)]
+    Supervise(ServerArgs),
+    /// Download a release into a bundle for offline installs
+    Bundle(BundleArgs),
+    /// Read and change the configuration of limit-server
+    Config(ConfigArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct InstallArgs {
+    /// Directory to install limit-server into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Public key (or path to a minisign .pub file) to verify releases with
+    #[arg(long)]
+    pub trusted_key: Option<String>,
+    /// Extract the AppImage and run it without FUSE,
+    /// this is done automatically if FUSE is unavailable
+    #[arg(long)]
+    pub extract: bool,
+    /// Install missing dependencies like FUSE with the package manager of the system,
+    /// this requires Root
+    #[arg(long)]
+    pub install_deps: bool,
+    /// Install from a bundle created by `limit-up bundle` instead of downloading
+    #[arg(long, value_name = "PATH")]
+    pub from_bundle: Option<PathBuf>,
+    /// Print what would be done without doing it
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub version: VersionArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct UpdateArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Public key (or path to a minisign .pub file) to verify releases with
+    #[arg(long)]
+    pub trusted_key: Option<String>,
+    /// Print what would be done without doing it
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub version: VersionArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct UninstallArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Remove user data and configuration as well
+    #[arg(long)]
+    pub purge: bool,
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Print what would be removed without removing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct StatusArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Print the status as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct DoctorArgs {
+    /// Directory to install limit-server into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ServiceArgs {
+    #[clap(subcommand)]
+    pub cmd: ServiceCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceCommand {
+    /// Install and enable a systemd or OpenRC service running limit-server
+    Install(ServiceInstallArgs),
+    /// Disable and remove the service
+    Remove(ServiceRemoveArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ServiceInstallArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Install a service of the current user instead of a system one
+    #[arg(long)]
+    pub user: bool,
+    /// Working directory of limit-server [default: the install root]
+    #[arg(long, value_name = "DIR")]
+    pub working_directory: Option<PathBuf>,
+    /// Environment variable of limit-server, can be given multiple times
+    #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_env)]
+    pub environment: Vec<(String, String)>,
+    /// User a system service runs limit-server as [default: the user installing it]
+    #[arg(long, value_name = "USER", conflicts_with = "user")]
+    pub run_as: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ServiceRemoveArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Remove the service of the current user instead of the system one
+    #[arg(long)]
+    pub user: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ServerArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct LogsArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Number of lines to print
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub lines: usize,
+    /// Keep printing new lines
+    #[arg(short, long)]
+    pub follow: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ConfigArgs {
+    #[clap(subcommand)]
+    pub cmd: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the value of a key like `server.port`, or its default value if it is not set
+    Get(ConfigGetArgs),
+    /// Change the value of a key, comments and other keys in the file are kept
+    Set(ConfigSetArgs),
+    /// Check the configuration file
+    Validate(ServerArgs),
+    /// Open the configuration file in $VISUAL or $EDITOR and check it afterwards
+    Edit(ServerArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ConfigGetArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Dotted key, like server.port
+    pub key: String,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ConfigSetArgs {
+    /// Directory limit-server was installed into [default: ~/.limit-lab]
+    #[arg(long)]
+    pub install_root: Option<String>,
+    /// Dotted key, like server.port
+    pub key: String,
+    pub value: String,
+}
+
+// parse an environment variable given as KEY=VALUE
+fn parse_env(env: &str) -> Result<(String, String), String> {
+    match env.split_once('=') {
+        Some((key, value)) if is_env_name(key) => Ok((key.to_string(), value.to_string())),
+        Some((key, _)) => Err(tr!(
+            "{} is not a valid name of an environment variable, \
+            which consists of letters, digits and underscores and doesn't start with a digit",
+            key
+        )
+        .to_string()),
+        None => Err(tr!("Expected KEY=VALUE, got {}", env).to_string()),
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct BundleArgs {
+    /// Path of the bundle [default: limit-server-<TAG>-<PLATFORM>.tar.gz]
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+    /// Public key (or path to a minisign .pub file) to verify releases with
+    #[arg(long)]
+    pub trusted_key: Option<String>,
+    #[command(flatten)]
+    pub version: VersionArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct VersionArgs {
+    /// Release tag to install, like v0.1.0
+    #[arg(long = "version", value_name = "TAG", conflicts_with = "channel")]
+    pub tag: Option<String>,
+    /// Release channel to follow: stable, prerelease or nightly
+    #[arg(long, default_value_t = Channel::Stable)]
+    pub channel: Channel,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct NetworkArgs {
+    /// Base URL of a release mirror (http(s):// or file://), can be given multiple times,
+    /// mirrors are tried in order and `github` stands for the official releases
+    #[arg(
+        long = "mirror",
+        value_name = "URL",
+        env = "LIMIT_UP_MIRRORS",
+        value_delimiter = ','
+    )]
+    pub mirrors: Vec<String>,
+    /// URL of a git mirror of limit-server, can be given multiple times
+    #[arg(long = "git-mirror", value_name = "URL")]
+    pub git_mirrors: Vec<String>,
+    /// Proxy for all requests, like http://proxy.example.com:8080
+    #[arg(long, env = "HTTPS_PROXY")]
+    pub proxy: Option<String>,
+    /// Comma separated hosts that bypass the proxy
+    #[arg(long, env = "NO_PROXY")]
+    pub no_proxy: Option<String>,
+    /// PEM file of extra trusted root certificates, can be given multiple times
+    #[arg(long, value_name = "PEM")]
+    pub ca_file: Vec<PathBuf>,
+    /// Seconds to wait for a connection to be established
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub connect_timeout: u64,
+    /// Seconds to wait for the next piece of a response
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    pub read_timeout: u64,
+    /// Maximum attempts of a network operation before giving up
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub retries: u32,
+    /// Seconds to wait before the first retry, doubled on every retry
+    #[arg(long, value_name = "SECONDS", default_value_t = 1.0)]
+    pub retry_backoff: f64,
+    /// Do not randomize delays between retries
+    #[arg(long)]
+    pub no_jitter: bool,
 }
@@ -0,0 +1,41 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{
+        installer::{self, InstallConfig},
+        RT,
+    },
+    BundleArgs,
+};
+
+// download a release into a bundle without TUI
+pub fn bundle(args: &BundleArgs) -> ExitCode {
+    let mut config = InstallConfig {
+        trusted_key: args.trusted_key.clone(),
+        version: super::version(&args.version),
+        ..Default::default()
+    };
+    super::apply_network_args(&mut config, &args.network);
+
+    println!("{}", tr!("Downloading limit-server..."));
+
+    let output = args.output.clone();
+
+    match RT.block_on(super::with_events(|events| {
+        installer::bundle(config, output, events)
+    })) {
+        Ok(path) => {
+            println!(
+                "{}",
+                tr!("Bundle has been saved to {}", path.display().to_string())
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
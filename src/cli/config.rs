@@ -0,0 +1,102 @@
+use std::{env, process::ExitCode};
+
+use r18::tr;
+
+use crate::{
+    core::config::{ConfigFile, ServerConfig},
+    ConfigArgs, ConfigCommand, ConfigGetArgs, ConfigSetArgs, ServerArgs,
+};
+
+// read and change the configuration of limit-server without TUI
+pub fn config(args: &ConfigArgs) -> ExitCode {
+    let res = match &args.cmd {
+        ConfigCommand::Get(args) => get(args),
+        ConfigCommand::Set(args) => set(args),
+        ConfigCommand::Validate(args) => validate(args),
+        ConfigCommand::Edit(args) => edit(args),
+    };
+
+    match res {
+        Ok(code) => code,
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn get(args: &ConfigGetArgs) -> crate::Result<ExitCode> {
+    let root = super::install_config(&args.install_root).install_root;
+
+    println!("{}", ConfigFile::open(root)?.get(&args.key)?);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn set(args: &ConfigSetArgs) -> crate::Result<ExitCode> {
+    let root = super::install_config(&args.install_root).install_root;
+
+    let mut file = ConfigFile::open(root)?;
+    file.set(&args.key, &args.value)?;
+    file.save()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: &ServerArgs) -> crate::Result<ExitCode> {
+    let root = super::install_config(&args.install_root).install_root;
+
+    check(&ConfigFile::open(root)?)
+}
+
+fn edit(args: &ServerArgs) -> crate::Result<ExitCode> {
+    let root = super::install_config(&args.install_root).install_root;
+
+    // start with the default configuration
+    if !ServerConfig::path(&root).exists() {
+        ServerConfig::default().save(&root)?;
+    }
+
+    let path = ServerConfig::path(&root);
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // the editor may come with arguments, like `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|e| tr!("Unable to run {}: {}", program, &e.to_string()).to_string())?;
+
+    if !status.success() {
+        Err(tr!("{} failed ({})", program, &status.to_string()).to_string())?;
+    }
+
+    check(&ConfigFile::open(root)?)
+}
+
+// print problems of the configuration file
+fn check(file: &ConfigFile) -> crate::Result<ExitCode> {
+    let path = file.path().display().to_string();
+
+    if !file.path().exists() {
+        Err(tr!("{} doesn't exist", &path).to_string())?;
+    }
+
+    let problems = file.problems()?;
+    if problems.is_empty() {
+        println!("{}", tr!("{} is valid", &path));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    eprintln!("{}", tr!("{} has problems:", &path));
+    for problem in problems {
+        eprintln!("  {}", problem);
+    }
+
+    Ok(ExitCode::FAILURE)
+}
@@ -0,0 +1,38 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{
+        installer::{self, doctor::Level},
+        RT,
+    },
+    DoctorArgs,
+};
+
+// run preflight checks and print their results, fails if any check failed
+pub fn doctor(args: &DoctorArgs) -> ExitCode {
+    let mut config = super::install_config(&args.install_root);
+    super::apply_network_args(&mut config, &args.network);
+
+    let checks = match RT.block_on(installer::doctor(&config)) {
+        Ok(checks) => checks,
+        Err(e) => {
+            super::report_error(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    checks.iter().for_each(|check| println!("{}", check));
+
+    match checks.iter().map(|check| check.level).max() {
+        Some(Level::Fail) => {
+            println!(
+                "{}",
+                tr!("limit-server can't be installed, see above for details")
+            );
+            ExitCode::FAILURE
+        }
+        _ => ExitCode::SUCCESS,
+    }
+}
@@ -0,0 +1,56 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{
+        installer::{self, InstallConfig},
+        RT,
+    },
+    InstallArgs,
+};
+
+// install limit-server without TUI
+pub fn install(args: &InstallArgs) -> ExitCode {
+    let mut config = super::install_config(&args.install_root);
+    config.trusted_key = args.trusted_key.clone();
+    config.version = super::version(&args.version);
+    config.bundle = args.from_bundle.clone();
+    config.extract = args.extract.then_some(true);
+    config.install_dependencies = args.install_deps;
+    super::apply_network_args(&mut config, &args.network);
+
+    if args.dry_run {
+        return dry_run(config);
+    }
+
+    println!("{}", tr!("Installing limit-server..."));
+
+    match RT.block_on(super::with_events(|events| {
+        installer::install(config, events)
+    })) {
+        Ok(_) => {
+            println!("{}", tr!("limit-server has been installed successfully"));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// print what installing would do
+fn dry_run(config: InstallConfig) -> ExitCode {
+    match RT.block_on(installer::install_plan(&config)) {
+        Ok(plan) => {
+            println!("{}", tr!("Dry run, the following actions would be taken:"));
+            super::print_plan(&plan);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,150 @@
+mod_use::mod_use!(install, update, uninstall, status, doctor, bundle, config);
+
+#[cfg(unix)]
+mod_use::mod_use!(service, server);
+
+use std::{
+    future::Future,
+    io::{self, Write},
+    time::Duration,
+};
+
+use r18::tr;
+use tokio::{join, sync::mpsc::UnboundedReceiver};
+
+use crate::{
+    core::{
+        helper::Help,
+        installer::{Action, Events, InstallConfig, InstallEvent, Stream},
+        release::{Mirror, Version},
+    },
+    NetworkArgs, VersionArgs,
+};
+
+// build install config from the install root given by the user
+fn install_config(install_root: &Option<String>) -> InstallConfig {
+    let mut config = InstallConfig::default();
+    if let Some(root) = install_root {
+        config.install_root = root.clone();
+    }
+
+    config
+}
+
+// return the release chosen by the user
+fn version(args: &VersionArgs) -> Version {
+    match &args.tag {
+        Some(tag) => Version::Tag(tag.clone()),
+        None => Version::Latest(args.channel),
+    }
+}
+
+// apply network options given by the user
+fn apply_network_args(config: &mut InstallConfig, args: &NetworkArgs) {
+    if !args.mirrors.is_empty() {
+        config.mirrors = args.mirrors.iter().map(|m| Mirror::parse(m)).collect();
+    }
+
+    if !args.git_mirrors.is_empty() {
+        config.git_mirrors = args.git_mirrors.clone();
+    }
+
+    config.http.proxy = args.proxy.clone().filter(|proxy| !proxy.is_empty());
+    config.http.no_proxy = args.no_proxy.clone();
+    config.http.ca_files = args.ca_file.clone();
+    config.http.connect_timeout = Duration::from_secs(args.connect_timeout);
+    config.http.read_timeout = Duration::from_secs(args.read_timeout);
+
+    config.retry.attempts = args.retries.max(1);
+    config.retry.backoff = Duration::from_secs_f64(args.retry_backoff.max(0.0));
+    config.retry.jitter = !args.no_jitter;
+}
+
+// ask the user a yes/no question, default is no
+fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+// print planned actions, one per line
+fn print_plan(plan: &[Action]) {
+    for action in plan {
+        println!("    {}", action.to_string().replace('\n', "\n    "));
+    }
+}
+
+// report a command that is not available on this platform
+#[cfg(not(unix))]
+pub fn unsupported() -> std::process::ExitCode {
+    let e: crate::Error = tr!("Unsupported platform").into();
+    report_error(&e);
+
+    std::process::ExitCode::FAILURE
+}
+
+// print an error and a help message if we have one for it
+fn report_error(e: &crate::Error) {
+    eprintln!("{}", tr!("Error: {}", e.to_string()));
+
+    if e.is::<reqwest::Error>() {
+        eprintln!("{}", Help::Network);
+    }
+}
+
+// run an installer and print its events while it runs
+async fn with_events<T, Fut>(run: impl FnOnce(Events) -> Fut) -> crate::Result<T>
+where
+    Fut: Future<Output = crate::Result<T>>,
+{
+    let (events, receiver) = Events::channel();
+    let (res, _) = join!(run(events), print_events(receiver));
+
+    res
+}
+
+// print steps and output of the installer on their own lines,
+// progress is kept on the last line until something else is printed,
+// errors are left to the caller
+async fn print_events(mut receiver: UnboundedReceiver<InstallEvent>) {
+    let mut progress = None;
+
+    while let Some(event) = receiver.recv().await {
+        let (stream, line) = match event {
+            InstallEvent::Progress { current, total } => {
+                let percent = current * 100 / total.max(1);
+
+                if progress != Some(percent) {
+                    print!("\r{:>3}%", percent);
+                    io::stdout().flush().ok();
+                    progress = Some(percent);
+                }
+
+                continue;
+            }
+            InstallEvent::StepStarted(step) => (Stream::Stdout, step),
+            InstallEvent::Log { stream, line } => (stream, line),
+            InstallEvent::Warning(message) => {
+                (Stream::Stderr, tr!("Warning: {}", message).to_string())
+            }
+            InstallEvent::StepFinished(_) | InstallEvent::Error(_) => continue,
+        };
+
+        // clear the progress, it is printed again with the next update
+        if progress.take().is_some() {
+            print!("\r    \r");
+        }
+
+        match stream {
+            Stream::Stdout => println!("{}", line),
+            Stream::Stderr => eprintln!("{}", line),
+        }
+    }
+
+    if progress.is_some() {
+        println!();
+    }
+}
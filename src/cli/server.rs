@@ -0,0 +1,91 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{
+        service::{self, InitSystem},
+        RT,
+    },
+    LogsArgs, Result, ServerArgs,
+};
+
+// print the outcome of a lifecycle command
+fn report<T>(res: Result<T>, message: impl FnOnce(T) -> String) -> ExitCode {
+    match res {
+        Ok(value) => {
+            println!("{}", message(value));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// tell how limit-server runs
+fn run_by(init_system: InitSystem) -> String {
+    match init_system {
+        InitSystem::Systemd(_) => tr!("by systemd").to_string(),
+        InitSystem::OpenRc => tr!("by OpenRC").to_string(),
+        InitSystem::Supervisor => tr!("by limit-up").to_string(),
+    }
+}
+
+// start limit-server without TUI
+pub fn start(args: &ServerArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    report(RT.block_on(service::start(&config)), |init_system| {
+        tr!("limit-server has been started {}", run_by(init_system)).to_string()
+    })
+}
+
+// stop limit-server without TUI
+pub fn stop(args: &ServerArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    report(
+        RT.block_on(service::stop(&config)),
+        |running| match running {
+            Some(false) => tr!("limit-server is not running").to_string(),
+            _ => tr!("limit-server has been stopped").to_string(),
+        },
+    )
+}
+
+// restart limit-server without TUI
+pub fn restart(args: &ServerArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    report(RT.block_on(service::restart(&config)), |init_system| {
+        tr!("limit-server has been restarted {}", run_by(init_system)).to_string()
+    })
+}
+
+// print logs of limit-server
+pub fn logs(args: &LogsArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    match RT.block_on(service::logs(&config, args.lines, args.follow)) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// run limit-server under the supervisor in the foreground
+pub fn supervise(args: &ServerArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    match RT.block_on(service::supervise(&config)) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
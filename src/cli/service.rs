@@ -0,0 +1,73 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{
+        service::{self, Scope, ServiceConfig},
+        RT,
+    },
+    ServiceArgs, ServiceCommand, ServiceInstallArgs, ServiceRemoveArgs,
+};
+
+// manage the service of limit-server without TUI
+pub fn service(args: &ServiceArgs) -> ExitCode {
+    match &args.cmd {
+        ServiceCommand::Install(args) => install(args),
+        ServiceCommand::Remove(args) => remove(args),
+    }
+}
+
+// return the scope chosen by the user
+fn scope(user: bool) -> Scope {
+    match user {
+        true => Scope::User,
+        false => Scope::System,
+    }
+}
+
+fn install(args: &ServiceInstallArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+    let service = ServiceConfig {
+        scope: scope(args.user),
+        working_directory: args.working_directory.clone(),
+        environment: args.environment.clone(),
+        user: args.run_as.clone(),
+    };
+
+    match RT.block_on(service::install(&config, &service)) {
+        Ok(unit) => {
+            println!(
+                "{}",
+                tr!(
+                    "limit-server service has been installed to {}",
+                    unit.display().to_string()
+                )
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn remove(args: &ServiceRemoveArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    match RT.block_on(service::remove(&config, scope(args.user))) {
+        Ok(Some(unit)) => {
+            println!("{}", tr!("{} has been removed", unit.display().to_string()));
+            ExitCode::SUCCESS
+        }
+        Ok(None) => {
+            println!("{}", tr!("limit-server service is not installed"));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::installer::{self, Status},
+    StatusArgs,
+};
+
+// report health of the installation, fails if it is not healthy
+pub fn status(args: &StatusArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    let status = match installer::status(&config) {
+        Ok(status) => status,
+        Err(e) => {
+            super::report_error(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match args.json {
+        true => match serde_json::to_string_pretty(&status) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                super::report_error(&e.into());
+                return ExitCode::FAILURE;
+            }
+        },
+        false => print_status(&status),
+    }
+
+    match status.is_healthy() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
+
+// print the status for humans
+fn print_status(status: &Status) {
+    let Some(receipt) = &status.receipt else {
+        println!(
+            "{}",
+            tr!("limit-server is not installed in {}", &status.install_root)
+        );
+        return;
+    };
+
+    let yes_no_unknown = |value: Option<bool>, yes: &str, no: &str| match value {
+        Some(true) => yes.to_string(),
+        Some(false) => no.to_string(),
+        None => tr!("unknown").to_string(),
+    };
+
+    let installed_at = humantime::format_rfc3339_seconds(receipt.installed_at()).to_string();
+    let none = tr!("none").to_string();
+
+    println!(
+        "{}",
+        tr!(
+            "limit-server {} is installed in {}",
+            &receipt.version,
+            &status.install_root
+        )
+    );
+    println!("    {}", tr!("Source: {}", &receipt.source));
+    println!(
+        "    {}",
+        tr!(
+            "SHA-256: {}",
+            receipt.sha256.clone().unwrap_or_else(|| none.clone())
+        )
+    );
+    println!("    {}", tr!("Installed at: {}", installed_at));
+    println!(
+        "    {}",
+        tr!(
+            "Running: {}",
+            yes_no_unknown(status.running, tr!("yes"), tr!("no"))
+        )
+    );
+    println!(
+        "    {}",
+        tr!(
+            "Checksum: {}",
+            yes_no_unknown(status.checksum_ok, tr!("ok"), tr!("mismatch"))
+        )
+    );
+
+    println!("    {}", tr!("Files:"));
+    receipt
+        .files
+        .iter()
+        .for_each(|file| println!("        {}", file.display()));
+
+    println!("    {}", tr!("Dependencies:"));
+    match receipt.dependencies.is_empty() {
+        true => println!("        {}", none),
+        false => receipt
+            .dependencies
+            .iter()
+            .for_each(|dependency| println!("        {}", dependency)),
+    }
+
+    if !status.commands.is_empty() {
+        println!("    {}", tr!("Required commands:"));
+        status.commands.iter().for_each(|command| {
+            let path = match &command.path {
+                Some(path) => path.display().to_string(),
+                None => tr!("missing").to_string(),
+            };
+
+            println!("        {}: {}", command.name, path);
+        });
+    }
+}
@@ -0,0 +1,56 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{installer, RT},
+    UninstallArgs,
+};
+
+// uninstall limit-server without TUI
+pub fn uninstall(args: &UninstallArgs) -> ExitCode {
+    let config = super::install_config(&args.install_root);
+
+    let plan = match installer::uninstall_plan(&config, args.purge) {
+        Ok(plan) => plan,
+        Err(e) => {
+            super::report_error(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if plan.is_empty() {
+        println!(
+            "{}",
+            tr!("limit-server is not installed, nothing to remove")
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    if args.dry_run {
+        println!("{}", tr!("Dry run, the following actions would be taken:"));
+        super::print_plan(&plan);
+        return ExitCode::SUCCESS;
+    }
+
+    println!("{}", tr!("The following actions will be taken:"));
+    super::print_plan(&plan);
+
+    if !args.yes && !super::confirm(tr!("Continue?")) {
+        println!("{}", tr!("Uninstallation cancelled"));
+        return ExitCode::FAILURE;
+    }
+
+    match RT.block_on(super::with_events(|events| {
+        installer::uninstall(config, plan, events)
+    })) {
+        Ok(_) => {
+            println!("{}", tr!("limit-server has been uninstalled"));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
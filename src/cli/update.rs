@@ -0,0 +1,73 @@
+use std::process::ExitCode;
+
+use r18::tr;
+
+use crate::{
+    core::{
+        installer::{self, InstallConfig, UpdateResult},
+        RT,
+    },
+    UpdateArgs,
+};
+
+// update limit-server without TUI
+pub fn update(args: &UpdateArgs) -> ExitCode {
+    let mut config = super::install_config(&args.install_root);
+    config.trusted_key = args.trusted_key.clone();
+    config.version = super::version(&args.version);
+    super::apply_network_args(&mut config, &args.network);
+
+    println!("{}", tr!("Checking for updates..."));
+
+    if args.dry_run {
+        return dry_run(config);
+    }
+
+    match RT.block_on(super::with_events(|events| {
+        installer::update(config, events)
+    })) {
+        Ok(UpdateResult::UpToDate(version)) => {
+            println!("{}", tr!("limit-server is up to date ({})", version));
+            ExitCode::SUCCESS
+        }
+        Ok(UpdateResult::Updated { from, to }) => {
+            let from = from.unwrap_or_else(|| tr!("unknown version").to_string());
+            println!(
+                "{}",
+                tr!("limit-server has been updated from {} to {}", from, to)
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// print what updating would do
+fn dry_run(config: InstallConfig) -> ExitCode {
+    match RT.block_on(installer::update_plan(&config)) {
+        Ok((UpdateResult::UpToDate(version), _)) => {
+            println!("{}", tr!("limit-server is up to date ({})", version));
+            ExitCode::SUCCESS
+        }
+        Ok((UpdateResult::Updated { from, to }, plan)) => {
+            let from = from.unwrap_or_else(|| tr!("unknown version").to_string());
+            println!(
+                "{}",
+                tr!(
+                    "Dry run, limit-server would be updated from {} to {} with the following actions:",
+                    from,
+                    to
+                )
+            );
+            super::print_plan(&plan);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            super::report_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+
+/// Name of the checksum file attached to every release
+pub const CHECKSUM_FILE: &str = "SHA256SUMS";
+
+/// Find the checksum of `name` in a `sha256sum` style checksum file
+pub fn find_checksum(sums: &str, name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+
+        // `*` marks files that were hashed in binary mode
+        (file.trim_start().trim_start_matches('*') == name).then(|| hash.to_lowercase())
+    })
+}
+
+/// Return the hex encoded SHA-256 hash of a file
+pub fn sha256_file(path: impl AsRef<Path>) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Return the hex encoded SHA-256 hash of a file, or of a directory if `path` is one,
+/// which covers the names, link targets and contents of everything in it
+pub fn sha256_path(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    if !path.symlink_metadata()?.is_dir() {
+        return sha256_file(path);
+    }
+
+    let mut hasher = Sha256::new();
+    hash_dir(&mut hasher, path, Path::new(""))?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// feed the entries of `dir` into `hasher` in order of their names,
+// `prefix` is the path of `dir` relative to the directory being hashed
+fn hash_dir(hasher: &mut Sha256, dir: &Path, prefix: &Path) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = prefix.join(entry.file_name());
+        let kind = entry.file_type()?;
+
+        hasher.update(name.to_string_lossy().as_bytes());
+
+        if kind.is_symlink() {
+            hasher.update(b"\0link\0");
+            hasher.update(fs::read_link(&path)?.to_string_lossy().as_bytes());
+        } else if kind.is_dir() {
+            hasher.update(b"\0dir\0");
+            hash_dir(hasher, &path, &name)?;
+        } else {
+            hasher.update(b"\0file\0");
+            hasher.update(sha256_file(&path)?.as_bytes());
+        }
+
+        hasher.update(b"\0");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::{find_checksum, sha256_file, sha256_path};
+
+    #[test]
+    fn test_find_checksum() {
+        let sums = "0123abcd  limit_up-x86_64-linux-gnu\nABCDEF01 *limit_up-aarch64-linux-gnu\n";
+
+        assert_eq!(
+            find_checksum(sums, "limit_up-x86_64-linux-gnu").as_deref(),
+            Some("0123abcd")
+        );
+        assert_eq!(
+            find_checksum(sums, "limit_up-aarch64-linux-gnu").as_deref(),
+            Some("abcdef01")
+        );
+        assert_eq!(find_checksum(sums, "limit_up-x86_64-linux-musl"), None);
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let path = env::temp_dir().join("limit-up-sha256-test");
+        fs::write(&path, "limit").unwrap();
+
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "55ea09e5715d0a8d9d94018d473bf23b2d7e630c2adb1f1acad3bada74c6fd05"
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_sha256_path() {
+        let dir = env::temp_dir().join("limit-up-sha256-dir-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(dir.join("usr/bin")).unwrap();
+        fs::write(dir.join("usr/bin/limit"), "limit").unwrap();
+        fs::write(dir.join("AppRun"), "#!/bin/sh").unwrap();
+
+        let hash = sha256_path(&dir).unwrap();
+        assert_eq!(sha256_path(&dir).unwrap(), hash);
+
+        fs::write(dir.join("usr/bin/limit"), "changed").unwrap();
+        assert_ne!(sha256_path(&dir).unwrap(), hash);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
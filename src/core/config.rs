@@ -0,0 +1,538 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use r18::tr;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use super::tls;
+
+/// Configuration file of limit-server in the install root
+pub const CONFIG_FILE: &str = "limit-server.toml";
+
+// schemes of databases limit-server supports
+const DATABASE_SCHEMES: &[&str] = &["sqlite", "postgres", "postgresql", "mysql"];
+
+/// Configuration of limit-server, keys missing from the file take their default values
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub server: Server,
+    pub database: Database,
+    pub admin: Admin,
+    pub tls: Tls,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Server {
+    /// Name clients reach limit-server by
+    pub hostname: String,
+    /// Address to listen on
+    pub address: String,
+    pub port: u16,
+    /// Where limit-server keeps its data, relative to the install root
+    pub data_dir: PathBuf,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            hostname: "localhost".to_string(),
+            address: "0.0.0.0".to_string(),
+            port: 1313,
+            data_dir: PathBuf::from("data"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Database {
+    pub url: String,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://data/limit.db".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Admin {
+    pub username: String,
+    pub password: String,
+}
+
+impl Default for Admin {
+    fn default() -> Self {
+        Self {
+            username: "admin".to_string(),
+            password: String::new(),
+        }
+    }
+}
+
+/// TLS is disabled if neither the certificate nor the private key is set
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tls {
+    /// Certificate chain in PEM, relative to the install root
+    pub cert: PathBuf,
+    /// Private key in PEM, relative to the install root
+    pub key: PathBuf,
+}
+
+impl Tls {
+    pub fn is_enabled(&self) -> bool {
+        !self.cert.as_os_str().is_empty() || !self.key.as_os_str().is_empty()
+    }
+}
+
+/// A problem found in the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Dotted path of the offending key, such as `server.port`
+    pub key: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+impl ServerConfig {
+    /// Return the path of the configuration file in `root`
+    pub fn path(root: impl AsRef<Path>) -> PathBuf {
+        root.as_ref().join(CONFIG_FILE)
+    }
+
+    /// Read the configuration in `root`, `None` if there isn't one
+    pub fn load(root: impl AsRef<Path>) -> crate::Result<Option<Self>> {
+        let file = ConfigFile::open(root)?;
+
+        match file.path().exists() {
+            true => file.config().map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// Write the configuration into `root`,
+    /// comments and unknown keys of an existing file are kept
+    pub fn save(&self, root: impl AsRef<Path>) -> crate::Result<PathBuf> {
+        let mut file = ConfigFile::open(root)?;
+        file.update(self)?;
+        file.save()?;
+
+        Ok(file.path)
+    }
+
+    /// Check the configuration, returns every problem found
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut problem = |key, message: String| problems.push(Problem { key, message });
+
+        let hostname = &self.server.hostname;
+        if hostname.is_empty() || hostname.contains(|c: char| c.is_whitespace() || c == '/') {
+            problem(
+                "server.hostname",
+                tr!("Invalid hostname {}", hostname).to_string(),
+            );
+        }
+
+        if self.server.address.parse::<IpAddr>().is_err() {
+            problem(
+                "server.address",
+                tr!("Invalid listen address {}", &self.server.address).to_string(),
+            );
+        }
+
+        if self.server.port == 0 {
+            problem(
+                "server.port",
+                tr!("Port must be between 1 and 65535").to_string(),
+            );
+        }
+
+        if self.server.data_dir.as_os_str().is_empty() {
+            problem(
+                "server.data_dir",
+                tr!("Data directory can't be empty").to_string(),
+            );
+        }
+
+        match Url::parse(&self.database.url) {
+            Ok(url) if DATABASE_SCHEMES.contains(&url.scheme()) => {}
+            _ => problem(
+                "database.url",
+                tr!(
+                    "Unsupported database URL {}, expected sqlite://, postgres:// or mysql://",
+                    &self.database.url
+                )
+                .to_string(),
+            ),
+        }
+
+        let username = &self.admin.username;
+        if username.is_empty()
+            || !username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            problem(
+                "admin.username",
+                tr!("Admin username may only contain letters, digits, _ and -").to_string(),
+            );
+        }
+
+        if self.admin.password.chars().count() < 8 {
+            problem(
+                "admin.password",
+                tr!("Admin password must be at least 8 characters").to_string(),
+            );
+        }
+
+        if self.tls.is_enabled() {
+            if self.tls.cert.as_os_str().is_empty() {
+                problem(
+                    "tls.cert",
+                    tr!("A certificate is required along with the private key").to_string(),
+                );
+            }
+
+            if self.tls.key.as_os_str().is_empty() {
+                problem(
+                    "tls.key",
+                    tr!("A private key is required along with the certificate").to_string(),
+                );
+            }
+        }
+
+        problems
+    }
+
+    /// Check files the configuration refers to, relative paths are resolved against `root`
+    pub fn file_problems(&self, root: impl AsRef<Path>) -> Vec<Problem> {
+        let tls = &self.tls;
+        if tls.cert.as_os_str().is_empty() || tls.key.as_os_str().is_empty() {
+            return Vec::new();
+        }
+
+        let root = root.as_ref();
+        match tls::check_pair(&root.join(&tls.cert), &root.join(&tls.key)) {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![Problem {
+                key: "tls.cert",
+                message: e.to_string(),
+            }],
+        }
+    }
+
+    /// Check the configuration, all problems found are reported at once
+    pub fn validate(&self) -> crate::Result<()> {
+        let problems = self.problems();
+
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(problems
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .into()),
+        }
+    }
+}
+
+/// The configuration file in an install root, editing it keeps comments,
+/// formatting and keys limit-up doesn't know about
+pub struct ConfigFile {
+    path: PathBuf,
+    doc: DocumentMut,
+}
+
+impl ConfigFile {
+    /// Read the configuration file in `root`, it is empty if there isn't one
+    pub fn open(root: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = ServerConfig::path(root);
+
+        let doc = match fs::read_to_string(&path) {
+            Ok(text) => text.parse::<DocumentMut>().map_err(|e| {
+                tr!(
+                    "Invalid configuration file {}: {}",
+                    path.display().to_string(),
+                    e.to_string()
+                )
+                .to_string()
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => DocumentMut::new(),
+            Err(e) => Err(e)?,
+        };
+
+        Ok(Self { path, doc })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check the configuration and files it refers to
+    pub fn problems(&self) -> crate::Result<Vec<Problem>> {
+        let config = self.config()?;
+        let root = self.path.parent().unwrap_or(Path::new("."));
+
+        let mut problems = config.problems();
+        problems.extend(config.file_problems(root));
+
+        Ok(problems)
+    }
+
+    /// Parse the file into the typed configuration
+    pub fn config(&self) -> crate::Result<ServerConfig> {
+        toml_edit::de::from_document(self.doc.clone()).map_err(|e| {
+            tr!(
+                "Invalid configuration file {}: {}",
+                self.path.display().to_string(),
+                e.to_string()
+            )
+            .into()
+        })
+    }
+
+    /// Return the value of a dotted key, the default value if it is not in the file
+    pub fn get(&self, key: &str) -> crate::Result<String> {
+        let defaults = toml_edit::ser::to_document(&ServerConfig::default())?;
+
+        let item = lookup(self.doc.as_item(), key)
+            .or_else(|| lookup(defaults.as_item(), key))
+            .ok_or_else(|| tr!("Unknown key {}", key).to_string())?;
+
+        Ok(match item {
+            Item::Value(Value::String(s)) => s.value().clone(),
+            Item::Value(value) => value.clone().decorated("", "").to_string(),
+            item => item.to_string().trim().to_string(),
+        })
+    }
+
+    /// Change the value of a dotted key limit-up knows about,
+    /// the value is rejected if it is invalid for the key
+    pub fn set(&mut self, key: &str, value: &str) -> crate::Result<()> {
+        // a broken file has to be fixed by editing it
+        self.config()?;
+
+        let defaults = toml_edit::ser::to_document(&ServerConfig::default())?;
+
+        // values take the type of the default value
+        let new = match lookup(defaults.as_item(), key) {
+            Some(Item::Value(Value::String(_))) => Value::from(value),
+            Some(Item::Value(Value::Integer(_))) => value
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| tr!("{} expects a number", key).to_string())?,
+            _ => Err(tr!("Unknown key {}", key).to_string())?,
+        };
+
+        let mut file = Self {
+            path: self.path.clone(),
+            doc: self.doc.clone(),
+        };
+
+        let (sections, name) = key.rsplit_once('.').unwrap_or(("", key));
+        let mut table: &mut dyn TableLike = file.doc.as_table_mut();
+        for section in sections.split('.').filter(|s| !s.is_empty()) {
+            table = table
+                .entry(section)
+                .or_insert(toml_edit::table())
+                .as_table_like_mut()
+                .ok_or_else(|| tr!("{} is not a table", section).to_string())?;
+        }
+
+        match table.get_mut(name) {
+            Some(item) => replace(item, new),
+            None => {
+                table.insert(name, Item::Value(new));
+            }
+        }
+
+        // other keys may be broken already, only this one matters
+        let problems = file
+            .config()
+            .map_err(|_| tr!("Invalid value {} for {}", value, key).to_string())?
+            .problems();
+        if let Some(problem) = problems.into_iter().find(|p| p.key == key) {
+            Err(problem.to_string())?;
+        }
+
+        *self = file;
+
+        Ok(())
+    }
+
+    /// Write the typed configuration into the file
+    pub fn update(&mut self, config: &ServerConfig) -> crate::Result<()> {
+        let new = toml_edit::ser::to_document(config)?;
+        merge(self.doc.as_table_mut(), new.as_table());
+
+        Ok(())
+    }
+
+    /// Write the file, it is only readable by the owner since it holds the admin password
+    pub fn save(&self) -> crate::Result<()> {
+        write_private(&self.path, self.doc.to_string().as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// Write a file only readable by the owner
+pub fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+
+    // the mode is only given to new files, existing ones may be readable by others
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+
+    file.write_all(data)
+}
+
+// find the item of a dotted key
+fn lookup<'a>(item: &'a Item, key: &str) -> Option<&'a Item> {
+    key.split('.').try_fold(item, |item, key| item.get(key))
+}
+
+// replace a value, keeping the comments around it
+fn replace(item: &mut Item, mut value: Value) {
+    if let Some(old) = item.as_value() {
+        *value.decor_mut() = old.decor().clone();
+    }
+
+    *item = Item::Value(value);
+}
+
+// write values of `new` into `table`, leaving other keys alone
+fn merge(table: &mut dyn TableLike, new: &dyn TableLike) {
+    for (key, item) in new.iter() {
+        if let Some(old) = table.get_mut(key) {
+            match (old.as_table_like_mut(), item.as_table_like()) {
+                (Some(old), Some(new)) => merge(old, new),
+                _ => match item.as_value() {
+                    Some(value) => replace(old, value.clone()),
+                    None => *old = item.clone(),
+                },
+            }
+
+            continue;
+        }
+
+        table.insert(key, item.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix::prelude::PermissionsExt};
+
+    use super::{write_private, ConfigFile, ServerConfig};
+
+    #[test]
+    fn test_validate() {
+        let mut config = ServerConfig::default();
+
+        // the password has to be set
+        assert!(config.validate().is_err());
+
+        config.admin.password = "limit-lab".to_string();
+        assert!(config.validate().is_ok());
+
+        config.server.address = "localhost".to_string();
+        config.database.url = "redis://localhost".to_string();
+        let keys: Vec<_> = config.problems().into_iter().map(|p| p.key).collect();
+        assert_eq!(keys, ["server.address", "database.url"]);
+    }
+
+    #[test]
+    fn test_write_private() {
+        let path = env::temp_dir().join("limit-up-private-test");
+        fs::write(&path, "public").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"private").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "private");
+        assert_eq!(path.metadata().unwrap().permissions().mode() & 0o777, 0o600);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_save_config() {
+        let dir = env::temp_dir().join("limit-up-config-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        assert!(ServerConfig::load(&dir).unwrap().is_none());
+
+        let mut config = ServerConfig::default();
+        config.admin.password = "limit-lab".to_string();
+
+        let path = config.save(&dir).unwrap();
+        assert_eq!(path.metadata().unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(ServerConfig::load(&dir).unwrap(), Some(config));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_edit_config() {
+        let dir = env::temp_dir().join("limit-up-config-edit-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        let text = "# limit-server\n\n[server]\nport = 8080 # public port\nworkers = 4\n";
+        fs::write(ServerConfig::path(&dir), text).unwrap();
+
+        let mut file = ConfigFile::open(&dir).unwrap();
+        assert_eq!(file.get("server.port").unwrap(), "8080");
+        assert_eq!(file.get("server.address").unwrap(), "0.0.0.0");
+        assert_eq!(file.get("server.workers").unwrap(), "4");
+        assert!(file.get("server.threads").is_err());
+
+        file.set("server.port", "9090").unwrap();
+        file.set("admin.password", "12345678").unwrap();
+        assert!(file.set("server.port", "0").is_err());
+        assert!(file.set("server.port", "http").is_err());
+        assert!(file.set("server.workers", "8").is_err());
+        file.save().unwrap();
+
+        let text = fs::read_to_string(ServerConfig::path(&dir)).unwrap();
+        assert!(text.starts_with("# limit-server\n"));
+        assert!(text.contains("port = 9090 # public port\nworkers = 4\n"));
+        assert!(text.contains("password = \"12345678\""));
+
+        // saving the typed configuration keeps them too
+        let mut config = ServerConfig::load(&dir).unwrap().unwrap();
+        config.server.port = 7070;
+        config.save(&dir).unwrap();
+
+        let text = fs::read_to_string(ServerConfig::path(&dir)).unwrap();
+        assert!(text.contains("port = 7070 # public port\nworkers = 4\n"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use reqwest::{
+    header::{CONTENT_RANGE, ETAG, IF_RANGE, RANGE},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use super::http;
+
+/// Suffix of files that are still being downloaded
+pub const PARTIAL_SUFFIX: &str = ".partial";
+
+// what we know about a partial download, stored next to it
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PartialMeta {
+    url: String,
+    etag: String,
+    total: u64,
+}
+
+/// Return the path of the partial file for `target`
+pub fn partial_path(target: impl AsRef<Path>) -> PathBuf {
+    let mut path = target.as_ref().as_os_str().to_owned();
+    path.push(PARTIAL_SUFFIX);
+    path.into()
+}
+
+// return the path of the metadata file for a partial file
+fn meta_path(partial: &Path) -> PathBuf {
+    let mut path = partial.as_os_str().to_owned();
+    path.push(".meta");
+    path.into()
+}
+
+/// Return files a download of `target` may leave behind if it is interrupted
+pub fn leftovers(target: impl AsRef<Path>) -> [PathBuf; 2] {
+    let partial = partial_path(target);
+    let meta = meta_path(&partial);
+
+    [partial, meta]
+}
+
+// return the local path of a `file://` URL
+fn local_path(url: &str) -> Option<PathBuf> {
+    match url.starts_with("file://") {
+        true => reqwest::Url::parse(url).ok()?.to_file_path().ok(),
+        false => None,
+    }
+}
+
+/// Fetch a text file, `file://` URLs are supported
+pub async fn fetch_text(url: &str) -> crate::Result<String> {
+    if let Some(path) = local_path(url) {
+        return Ok(fs::read_to_string(path)?);
+    }
+
+    let resp = http::read(http::client().get(url).send())
+        .await?
+        .error_for_status()?;
+
+    http::read(resp.text()).await
+}
+
+/// Check that `url` can be reached, any response of the server will do,
+/// URLs other than `file://` and HTTP(S) ones can't be checked and are assumed reachable
+pub async fn probe(url: &str) -> crate::Result<()> {
+    if let Some(path) = local_path(url) {
+        fs::metadata(path)?;
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        http::read(http::client().head(url).send()).await?;
+    }
+
+    Ok(())
+}
+
+/// Download `url` to the partial file of `target`,
+/// an interrupted download is resumed if the server supports range requests.
+///
+/// The partial file is left in place once completed, it is up to the caller to
+/// verify and move it to `target`.
+pub async fn download(
+    url: &str,
+    target: impl AsRef<Path>,
+    callback: impl Fn(usize) + Send + 'static,
+) -> crate::Result<PathBuf> {
+    let partial = partial_path(target);
+    let meta_file = meta_path(&partial);
+
+    if let Some(path) = local_path(url) {
+        copy(&path, &partial, callback)?;
+        return Ok(partial);
+    }
+
+    // resume only if we know the partial file comes from the same resource
+    let meta = fs::read(&meta_file)
+        .ok()
+        .and_then(|meta| serde_json::from_slice::<PartialMeta>(&meta).ok())
+        .filter(|meta| meta.url == url);
+    let downloaded = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+
+    let client = http::client();
+    let mut req = client.get(url);
+    if let Some(meta) = meta.as_ref().filter(|_| downloaded > 0) {
+        req = req
+            .header(RANGE, format!("bytes={}-", downloaded))
+            .header(IF_RANGE, &meta.etag);
+    }
+
+    let mut resp = http::read(req.send()).await?;
+    if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        resp = http::read(client.get(url).send()).await?;
+    }
+    let mut resp = resp.error_for_status()?;
+
+    let resumed = match (resp.status(), &meta) {
+        (StatusCode::PARTIAL_CONTENT, Some(meta)) => resp
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|range| parse_content_range(range.to_str().ok()?))
+            .is_some_and(|(start, total)| start == downloaded && total == meta.total),
+        _ => false,
+    };
+
+    let (mut target, total, mut current) = match resumed {
+        true => (
+            OpenOptions::new().append(true).open(&partial)?,
+            meta.unwrap().total,
+            downloaded,
+        ),
+        false => {
+            // the server doesn't support resuming or the resource has changed
+            if resp.status() == StatusCode::PARTIAL_CONTENT {
+                resp = http::read(client.get(url).send())
+                    .await?
+                    .error_for_status()?;
+            }
+
+            let total = resp
+                .content_length()
+                .ok_or("Unknown size when downloading Appimage".to_string())?;
+
+            match resp.headers().get(ETAG).and_then(|etag| etag.to_str().ok()) {
+                Some(etag) => fs::write(
+                    &meta_file,
+                    serde_json::to_vec(&PartialMeta {
+                        url: url.to_string(),
+                        etag: etag.to_string(),
+                        total,
+                    })?,
+                )?,
+                None => {
+                    fs::remove_file(&meta_file).ok();
+                }
+            }
+
+            (File::create(&partial)?, total, 0)
+        }
+    };
+
+    let progress = |current: u64| (current as f64 / total as f64 * 100.0) as usize;
+    let mut old_progress = progress(current);
+    callback(old_progress);
+
+    while let Some(chunk) = http::read(resp.chunk()).await? {
+        target.write_all(&chunk)?;
+
+        current += chunk.len() as u64;
+        let new_progress = progress(current);
+        if new_progress != old_progress {
+            callback(new_progress);
+            old_progress = new_progress;
+        }
+    }
+
+    target.sync_all()?;
+    fs::remove_file(&meta_file).ok();
+
+    Ok(partial)
+}
+
+// copy a local file with progress
+fn copy(from: &Path, to: &Path, callback: impl Fn(usize)) -> crate::Result<()> {
+    let mut source = File::open(from)?;
+    let mut target = File::create(to)?;
+
+    let total = source.metadata()?.len().max(1);
+    let mut current = 0;
+    let mut old_progress = 0;
+    let mut buf = vec![0; 64 * 1024];
+
+    callback(old_progress);
+
+    loop {
+        let n = source.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        target.write_all(&buf[..n])?;
+
+        current += n as u64;
+        let new_progress = (current as f64 / total as f64 * 100.0) as usize;
+        if new_progress != old_progress {
+            callback(new_progress);
+            old_progress = new_progress;
+        }
+    }
+
+    target.sync_all()?;
+
+    Ok(())
+}
+
+// parse `bytes <start>-<end>/<total>` into start and total
+fn parse_content_range(range: &str) -> Option<(u64, u64)> {
+    let (range, total) = range.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_content_range, partial_path};
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 100-199/200"), Some((100, 200)));
+        assert_eq!(parse_content_range("bytes 0-0/1"), Some((0, 1)));
+        assert_eq!(parse_content_range("bytes */200"), None);
+        assert_eq!(parse_content_range("bytes 100-199/*"), None);
+    }
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            partial_path("/opt/limit-server.Appimage").to_str(),
+            Some("/opt/limit-server.Appimage.partial")
+        );
+    }
+}
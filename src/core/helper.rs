@@ -3,8 +3,15 @@ use r18::tr;
 
 #[allow(dead_code)]
 pub enum Help {
+    Checksum,
+    DiskSpace,
+    Fuse,
     Git,
     Network,
+    Permission,
+    Root,
+    Signature,
+    Tools,
 }
 
 static CONTACT_US: Lazy<&'static str> =
@@ -17,10 +24,39 @@ impl Help {
                 "Please confirm the network settings and try again, {}",
                 &*CONTACT_US
             ),
+            Help::Checksum => tr!(
+                "The downloaded file may be corrupted or tampered with, please try again, {}",
+                &*CONTACT_US
+            ),
             Help::Git => tr!(
                 "Check your network settings or delete the repository and try again, {}",
                 &*CONTACT_US
             ),
+            Help::DiskSpace => tr!(
+                "Free up some space or choose another install root, {}",
+                &*CONTACT_US
+            ),
+            Help::Fuse => tr!(
+                "AppImages need FUSE to run, install the fuse package of your distribution, {}",
+                &*CONTACT_US
+            ),
+            Help::Permission => tr!(
+                "Choose an install root you have write access to, {}",
+                &*CONTACT_US
+            ),
+            Help::Root => tr!(
+                "Dependencies are installed with the package manager, which requires Root, please rerun as Root or install sudo, doas or pkexec, {}",
+                &*CONTACT_US
+            ),
+            Help::Tools => tr!(
+                "Install the missing tools with your package manager and try again, {}",
+                &*CONTACT_US
+            ),
+            Help::Signature => tr!(
+                "Make sure limit-server is downloaded from a trusted source, \
+                if you are using a private mirror, pass its public key with --trusted-key, {}",
+                &*CONTACT_US
+            ),
         }
         .to_string()
     }
@@ -31,3 +67,14 @@ impl std::fmt::Display for Help {
         write!(f, "{}", tr!("help: {}", self.info()))
     }
 }
+
+/// Return whether `name` is a valid name of an environment variable,
+/// which is `[A-Za-z_][A-Za-z0-9_]*` as shells accept it
+pub fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
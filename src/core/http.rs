@@ -0,0 +1,109 @@
+use std::{fs, future::Future, io, path::PathBuf, sync::RwLock, time::Duration};
+
+use once_cell::sync::Lazy;
+use r18::tr;
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+
+static HTTP_CLIENT: Lazy<RwLock<(Client, HttpConfig)>> = Lazy::new(|| {
+    let config = HttpConfig::default();
+    let client = config.build().expect("Failed to create HTTP client");
+
+    RwLock::new((client, config))
+});
+
+/// Settings of the HTTP client
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpConfig {
+    /// Proxy for all requests, system proxies are used if it is not set
+    pub proxy: Option<String>,
+    /// Comma separated hosts that bypass the proxy
+    pub no_proxy: Option<String>,
+    /// PEM files of extra trusted root certificates
+    pub ca_files: Vec<PathBuf>,
+    pub connect_timeout: Duration,
+    /// Maximum time to wait for the next piece of a response
+    pub read_timeout: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            no_proxy: None,
+            ca_files: Vec::new(),
+            connect_timeout: Duration::from_secs(30),
+            read_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Build an HTTP client with this config
+    pub fn build(&self) -> crate::Result<Client> {
+        let mut builder = Client::builder()
+            .user_agent(user_agent())
+            .connect_timeout(self.connect_timeout);
+
+        if let Some(proxy) = &self.proxy {
+            let no_proxy = match &self.no_proxy {
+                Some(no_proxy) => NoProxy::from_string(no_proxy),
+                None => NoProxy::from_env(),
+            };
+
+            builder = builder.proxy(Proxy::all(proxy)?.no_proxy(no_proxy));
+        }
+
+        for file in &self.ca_files {
+            let pem = fs::read(file).map_err(|e| {
+                tr!(
+                    "Can not read CA file {}: {}",
+                    file.display().to_string(),
+                    e.to_string()
+                )
+                .to_string()
+            })?;
+
+            for cert in Certificate::from_pem_bundle(&pem)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+/// `User-Agent` of limit-up, like `limit-up/0.1.0 (linux; x86_64)`
+pub fn user_agent() -> String {
+    format!(
+        "limit-up/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        env!("TARGET_OS"),
+        env!("TARGET_ARCH")
+    )
+}
+
+/// Rebuild the HTTP client if the config has changed
+pub fn configure(config: &HttpConfig) -> crate::Result<()> {
+    let mut http = HTTP_CLIENT.write().unwrap();
+
+    if http.1 != *config {
+        *http = (config.build()?, config.clone());
+    }
+
+    Ok(())
+}
+
+/// Return the HTTP client
+pub fn client() -> Client {
+    HTTP_CLIENT.read().unwrap().0.clone()
+}
+
+/// Wait for a part of a response, fail if it takes longer than the read timeout
+pub async fn read<T>(fut: impl Future<Output = reqwest::Result<T>>) -> crate::Result<T> {
+    let timeout = HTTP_CLIENT.read().unwrap().1.read_timeout;
+
+    match tokio::time::timeout(timeout, fut).await {
+        Ok(res) => Ok(res?),
+        Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "Read timed out").into()),
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    fmt,
+    iter::empty,
+    path::{Path, PathBuf},
+};
+
+use r18::tr;
+
+use super::{find_command, Events, InstallConfig, Stream};
+use crate::core::{download, helper::Help};
+
+const MIB: u64 = 1024 * 1024;
+
+/// Outcome of a check, ordered by severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Pass,
+    /// The install may work but something is likely to go wrong later
+    Warn,
+    /// The install is going to fail
+    Fail,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Pass => write!(f, "{}", tr!("pass")),
+            Level::Warn => write!(f, "{}", tr!("warn")),
+            Level::Fail => write!(f, "{}", tr!("fail")),
+        }
+    }
+}
+
+/// Result of a preflight check
+pub struct Check {
+    pub name: String,
+    pub level: Level,
+    pub message: String,
+    /// What the user can do about a warning or a failure
+    pub help: Option<Help>,
+}
+
+impl Check {
+    pub fn new(name: &str, level: Level, message: impl ToString, help: Option<Help>) -> Self {
+        Self {
+            name: name.to_string(),
+            level,
+            message: message.to_string(),
+            help,
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.level, self.name, self.message)?;
+
+        match &self.help {
+            Some(help) if self.level != Level::Pass => write!(f, "\n    {}", help),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Report `checks` as events and fail if any check failed
+pub fn preflight(checks: Vec<Check>, events: &Events) -> crate::Result<()> {
+    let mut failed = Vec::new();
+
+    for check in checks {
+        match check.level {
+            Level::Pass => events.log(Stream::Stdout, check.to_string()),
+            Level::Warn => events.warn(check.to_string()),
+            Level::Fail => failed.push(check.to_string()),
+        }
+    }
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(tr!("Preflight checks failed:\n\n{}", failed.join("\n")).into()),
+    }
+}
+
+// the nearest directory of `path` that exists, which is where it will be created in
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|path| path.exists())
+        .map(Path::to_path_buf)
+}
+
+/// Check that the install root can be created or written to
+pub fn check_install_root(config: &InstallConfig) -> Check {
+    let name = tr!("Install root");
+    let root = Path::new(&config.install_root);
+
+    let Some(dir) = existing_ancestor(root) else {
+        return Check::new(
+            name,
+            Level::Fail,
+            tr!("{} is not a valid path", &config.install_root),
+            Some(Help::Permission),
+        );
+    };
+
+    if !dir.is_dir() {
+        return Check::new(
+            name,
+            Level::Fail,
+            tr!("{} is not a directory", dir.display().to_string()),
+            Some(Help::Permission),
+        );
+    }
+
+    match writable(&dir) {
+        true => Check::new(
+            name,
+            Level::Pass,
+            tr!("{} is writable", dir.display().to_string()),
+            None,
+        ),
+        false => Check::new(
+            name,
+            Level::Fail,
+            tr!("Permission denied on {}", dir.display().to_string()),
+            Some(Help::Permission),
+        ),
+    }
+}
+
+// whether files can be created in a directory
+#[cfg(unix)]
+fn writable(dir: &Path) -> bool {
+    use nix::unistd::{access, AccessFlags};
+
+    access(dir, AccessFlags::W_OK | AccessFlags::X_OK).is_ok()
+}
+
+// there are no permission bits to check on windows, try creating a file instead
+#[cfg(not(unix))]
+fn writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".limit-up-{}", std::process::id()));
+
+    std::fs::File::create(&probe).is_ok() && std::fs::remove_file(&probe).is_ok()
+}
+
+// bytes available to unprivileged users on the filesystem of a directory
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Option<u64> {
+    let stat = nix::sys::statvfs::statvfs(dir).ok()?;
+
+    // field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let free = stat.blocks_available() as u64 * stat.fragment_size() as u64;
+
+    Some(free)
+}
+
+// free space is not known without the windows API, the check only warns then
+#[cfg(not(unix))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+/// Check free space in the install root, `required` is the size of the install
+pub fn check_disk_space(config: &InstallConfig, required: u64) -> Check {
+    let name = tr!("Disk space");
+
+    let free = existing_ancestor(Path::new(&config.install_root)).and_then(|dir| free_space(&dir));
+
+    let Some(free) = free else {
+        return Check::new(
+            name,
+            Level::Warn,
+            tr!("Unable to tell free space of {}", &config.install_root),
+            None,
+        );
+    };
+
+    let message = tr!(
+        "{} MiB free, {} MiB required",
+        (free / MIB).to_string(),
+        (required / MIB).to_string()
+    );
+
+    // leave some room for data and updates, which download next to the old binary
+    match free {
+        free if free < required => Check::new(name, Level::Fail, message, Some(Help::DiskSpace)),
+        free if free < required * 2 => {
+            Check::new(name, Level::Warn, message, Some(Help::DiskSpace))
+        }
+        _ => Check::new(name, Level::Pass, message, None),
+    }
+}
+
+/// Check that at least one of the sources can be reached,
+/// unreachable sources are fine as long as one of them works
+pub async fn check_network(sources: &[String]) -> Check {
+    let name = tr!("Network");
+
+    let mut unreachable = Vec::new();
+    for source in sources {
+        if let Err(e) = download::probe(source).await {
+            unreachable.push(format!("{} ({})", source, e));
+        }
+    }
+
+    match unreachable.len() {
+        0 => Check::new(
+            name,
+            Level::Pass,
+            tr!("{} reachable", sources.join(", ")),
+            None,
+        ),
+        n if n < sources.len() => Check::new(
+            name,
+            Level::Warn,
+            tr!("Unable to reach {}", unreachable.join(", ")),
+            Some(Help::Network),
+        ),
+        _ => Check::new(
+            name,
+            Level::Fail,
+            tr!("Unable to reach {}", unreachable.join(", ")),
+            Some(Help::Network),
+        ),
+    }
+}
+
+/// Check that the tools the installer runs are on `PATH`
+#[cfg_attr(not(any(target_os = "linux", target_os = "freebsd")), allow(dead_code))]
+pub fn check_tools(tools: &[&str]) -> Vec<Check> {
+    tools
+        .iter()
+        .map(
+            |tool| match find_command(tool, empty::<&str>()).into_iter().next() {
+                Some(path) => Check::new(tool, Level::Pass, path.display(), None),
+                None => Check::new(
+                    tool,
+                    Level::Fail,
+                    tr!("{} is not found in PATH", tool.to_string()),
+                    Some(Help::Tools),
+                ),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::{check_install_root, preflight, Check, Level};
+    use crate::core::installer::{Events, InstallConfig, InstallEvent};
+
+    #[test]
+    fn test_check_install_root() {
+        let dir = env::temp_dir().join("limit-up-doctor-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        // the install root is created in a writable directory
+        let mut config = InstallConfig {
+            install_root: dir.join("a/b").display().to_string(),
+            ..Default::default()
+        };
+        assert_eq!(check_install_root(&config).level, Level::Pass);
+
+        fs::write(dir.join("file"), "").unwrap();
+        config.install_root = dir.join("file/a").display().to_string();
+        assert_eq!(check_install_root(&config).level, Level::Fail);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_preflight() {
+        let check = |level| Check::new("test", level, "", None);
+
+        let (events, mut receiver) = Events::channel();
+
+        assert!(preflight(vec![check(Level::Pass), check(Level::Warn)], &events).is_ok());
+        assert!(matches!(receiver.try_recv(), Ok(InstallEvent::Log { .. })));
+        assert!(matches!(receiver.try_recv(), Ok(InstallEvent::Warning(_))));
+
+        assert!(preflight(vec![check(Level::Pass), check(Level::Fail)], &events).is_err());
+    }
+}
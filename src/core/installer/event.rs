@@ -0,0 +1,77 @@
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Stream a line of output was printed to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// What an installer reports while it runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallEvent {
+    /// A step has started, which is an action of the plan or a command it runs
+    StepStarted(String),
+    /// Overall progress, `current` out of `total`
+    Progress { current: usize, total: usize },
+    /// A line of output of the installer or of a command it runs
+    Log { stream: Stream, line: String },
+    /// Something went wrong, but the installer goes on
+    Warning(String),
+    /// A step has finished successfully
+    StepFinished(String),
+    /// The installer has failed, nothing is sent after it
+    Error(String),
+}
+
+/// Sends events of an installer over a channel,
+/// they are dropped if nobody receives them anymore
+#[derive(Debug, Clone)]
+pub struct Events(UnboundedSender<InstallEvent>);
+
+impl Events {
+    /// Create a channel for the events of an installer,
+    /// the receiver ends once the installer is done with the sender
+    pub fn channel() -> (Self, UnboundedReceiver<InstallEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        (Self(sender), receiver)
+    }
+
+    /// Return a sender whose events nobody receives
+    pub fn discard() -> Self {
+        Self::channel().0
+    }
+
+    pub fn send(&self, event: InstallEvent) {
+        self.0.send(event).ok();
+    }
+
+    /// Report progress in percent
+    pub fn progress(&self, percent: usize) {
+        self.send(InstallEvent::Progress {
+            current: percent,
+            total: 100,
+        });
+    }
+
+    pub fn log(&self, stream: Stream, line: impl Into<String>) {
+        self.send(InstallEvent::Log {
+            stream,
+            line: line.into(),
+        });
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.send(InstallEvent::Warning(message.into()));
+    }
+
+    /// Report the error if the installer has failed, the result is passed through
+    pub fn finish<T>(&self, res: crate::Result<T>) -> crate::Result<T> {
+        if let Err(e) = &res {
+            self.send(InstallEvent::Error(e.to_string()));
+        }
+
+        res
+    }
+}
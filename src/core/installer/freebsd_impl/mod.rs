@@ -1,131 +1,345 @@
-mod pkgmanager;
-
-use super::{find_command, InstallConfig};
+use super::{
+    doctor::{self, Check, Level},
+    find_command, Action, Events, InstallConfig, InstallEvent, Receipt, Stream,
+};
 use r18::tr;
 use std::{
     iter::empty,
     path::Path,
-    process::{ExitStatus, Stdio}, sync::Arc,
+    process::{ExitStatus, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 use tokio::{
-    io::{AsyncBufReadExt, BufReader},
+    join,
     process::{Child, Command},
-    select,
 };
 
-use crate::core::{helper::Help, installer::freebsd_impl::pkgmanager::PackageManager};
+use crate::core::{
+    helper::Help,
+    http,
+    installer::{
+        pkgmanager::{self, PackageManager},
+        privilege::Privilege,
+    },
+};
+
+// where the source code of limit-server is cloned into
+const REPO_DIR: &str = "limit-server";
 
-pub async fn install(
-    config: InstallConfig,
-    callback: impl Fn(usize, String, String) + Send + 'static,
-) -> crate::Result<()> {
-    let callback = Arc::new(callback);
-    let mut progress = 0;
+/// Commands limit-server installed as `receipt` says needs to run
+pub fn required_commands(_receipt: Option<&Receipt>) -> Vec<super::Command> {
+    ["iex", "git"]
+        .into_iter()
+        .map(super::Command::find)
+        .collect()
+}
+
+// tools the installer runs
+const TOOLS: &[&str] = &["git", "pkg"];
+
+// size of Elixir and the repo with some headroom
+const REQUIRED_SPACE: u64 = 500 * 1024 * 1024;
+
+/// Check whether limit-server can be installed and run
+pub async fn doctor(config: &InstallConfig) -> crate::Result<Vec<Check>> {
+    http::configure(&config.http)?;
+
+    let mut checks = vec![
+        doctor::check_install_root(config),
+        doctor::check_disk_space(config, REQUIRED_SPACE),
+        doctor::check_network(&config.git_mirrors).await,
+    ];
+
+    checks.extend(doctor::check_tools(TOOLS));
+    checks.push(check_root(config));
+
+    Ok(checks)
+}
+
+// Elixir is installed with the package manager, which requires Root
+fn check_root(config: &InstallConfig) -> Check {
+    let name = tr!("Privileges");
+
+    if !find_command("iex", empty::<&str>()).is_empty() {
+        return Check::new(
+            name,
+            Level::Pass,
+            tr!("Elixir is installed, Root is not required"),
+            None,
+        );
+    }
+
+    match Privilege::detect(config.elevation.clone()) {
+        Ok(privilege) => match privilege.tool() {
+            None => Check::new(name, Level::Pass, tr!("Running as Root"), None),
+            Some(tool) => Check::new(
+                name,
+                Level::Pass,
+                tr!("Commands that need Root will be run with {}", tool),
+                None,
+            ),
+        },
+        Err(_) => Check::new(
+            name,
+            Level::Fail,
+            tr!("Root is required, but none of sudo, doas and pkexec is found"),
+            Some(Help::Root),
+        ),
+    }
+}
+
+pub async fn install(config: InstallConfig, events: Events) -> crate::Result<()> {
+    let res = async {
+        doctor::preflight(doctor(&config).await?, &events)?;
+
+        let actions = install_plan(&config).await?;
+        execute(&config, actions, &events).await
+    }
+    .await;
+
+    events.finish(res)
+}
+
+/// Plan what `install` would do without changing anything
+pub async fn install_plan(config: &InstallConfig) -> crate::Result<Vec<Action>> {
+    let root = Path::new(&config.install_root);
+    let repo = root.join(REPO_DIR);
+    let mut actions = Vec::new();
+    let mut dependencies = Vec::new();
 
     // install Elixir
     if find_command("iex", empty::<&str>()).is_empty() {
-        install_elixir(&mut progress, callback.clone()).await?;
-    }
+        // fail early if there is no way to run the package manager as Root
+        Privilege::detect(config.elevation.clone())?;
+
+        let mgr = PackageManager::new()?;
+        let packages = vec!["elixir".to_string()];
 
-    progress = 50;
-    callback(progress, String::new(), String::new());
+        actions.push(Action::Packages {
+            manager: mgr.name(),
+            steps: mgr.install_steps(&packages)?,
+            packages,
+        });
+        dependencies.push("elixir".to_string());
+    }
 
     // install or update the server repo
-    clone_or_pull_repo(config, &mut progress, callback.clone()).await
+    match repo.exists() {
+        true => actions.push(Action::GitPull {
+            urls: config.git_mirrors.clone(),
+            path: repo,
+        }),
+        false => {
+            if !root.exists() {
+                actions.push(Action::CreateDir(root.to_path_buf()));
+            }
+
+            actions.push(Action::GitClone {
+                urls: config.git_mirrors.clone(),
+                path: repo,
+            });
+        }
+    }
+
+    // dependencies installed by earlier installs are kept,
+    // the revision and the mirror are known once cloned
+    if let Some(old) = Receipt::load(root)? {
+        for dependency in old.dependencies {
+            if !dependencies.contains(&dependency) {
+                dependencies.push(dependency);
+            }
+        }
+    }
+
+    let mut receipt = Receipt::new("", "");
+    receipt.files.push(REPO_DIR.into());
+    receipt.dependencies = dependencies;
+
+    actions.push(Action::WriteReceipt {
+        root: root.to_path_buf(),
+        receipt: Box::new(receipt),
+    });
+
+    Ok(actions)
+}
+
+// execute planned actions in order until one of them fails,
+// every action is a step of the events sent
+async fn execute(
+    config: &InstallConfig,
+    actions: Vec<Action>,
+    events: &Events,
+) -> crate::Result<()> {
+    let mut progress = 0;
+    // the mirror the repo was cloned or pulled from
+    let mut source = String::new();
+
+    for action in actions {
+        let step = action.to_string();
+        events.send(InstallEvent::StepStarted(step.clone()));
+
+        match action {
+            Action::Packages { steps, .. } => {
+                let privilege = Privilege::detect(config.elevation.clone())?;
+
+                pkgmanager::run(&privilege, &steps, progress..49, events)
+                    .await
+                    .map_err(|e| {
+                        tr!("{}\n\n{}", e.to_string(), Help::Network.to_string()).to_string()
+                    })?;
+            }
+            Action::CreateDir(path) => std::fs::create_dir_all(path)?,
+            Action::GitClone { urls, path } | Action::GitPull { urls, path } => {
+                progress = progress.max(50);
+                events.progress(progress);
+
+                source = clone_or_pull_repo(&urls, &path, &mut progress, events).await?;
+            }
+            Action::WriteReceipt { root, mut receipt } => {
+                let output = Command::new("git")
+                    .arg("-C")
+                    .arg(root.join(REPO_DIR))
+                    .args(["rev-parse", "HEAD"])
+                    .output()
+                    .await?;
+
+                receipt.version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                receipt.source = source.clone();
+                receipt.save(root)?;
+            }
+            action => {
+                Err(tr!("{} is not supported on this platform", action.to_string()).to_string())?
+            }
+        }
+
+        events.send(InstallEvent::StepFinished(step));
+    }
+
+    events.progress(100);
+
+    Ok(())
 }
 
 async fn trace_process(
     mut proc: Child,
     progress: &mut usize,
     max_progress: usize,
-    callback: Arc<impl Fn(usize, String, String) + Send + 'static>,
+    events: &Events,
     on_failed: impl FnOnce(ExitStatus) -> crate::Error,
 ) -> crate::Result<()> {
-    let mut stdout = BufReader::new(proc.stdout.take().unwrap());
-    let mut stderr = BufReader::new(proc.stderr.take().unwrap());
+    let stdout = proc.stdout.take().unwrap();
+    let stderr = proc.stderr.take().unwrap();
+    // every line of output is a sign of progress
+    let lines = AtomicUsize::new(*progress);
 
-    loop {
-        let mut out_buf = String::new();
-        let mut err_buf = String::new();
+    let (status, _, _) = join!(
+        proc.wait(),
+        pkgmanager::forward_lines(stdout, |line| {
+            let progress = (lines.load(Ordering::Relaxed) + 1).min(max_progress - 1);
+            lines.store(progress, Ordering::Relaxed);
 
-        select! {
-            _ = stdout.read_line(&mut out_buf) => {
-                if *progress < max_progress - 1 {
-                    *progress += 1;
-                }
+            events.progress(progress);
+            events.log(Stream::Stdout, line);
+        }),
+        pkgmanager::forward_lines(stderr, |line| events.log(Stream::Stderr, line)),
+    );
+    *progress = lines.into_inner();
+
+    let status = status?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(on_failed(status)),
+    }
+}
+
+// clone the repo, or pull it if it exists, returns the mirror it was cloned or pulled from
+async fn clone_or_pull_repo(
+    mirrors: &[String],
+    repo: &Path,
+    progress: &mut usize,
+    events: &Events,
+) -> crate::Result<String> {
+    let exists = repo.exists();
+    let mut last_error = None;
+
+    // try the mirrors in order until one of them succeeds
+    for mirror in mirrors {
+        let mut command = Command::new("git");
 
-                callback(*progress, out_buf, String::new());
-            },
-            _ = stderr.read_line(&mut err_buf) => {
-                callback(*progress, String::new(), err_buf);
-            },
-            status = proc.wait() => {
-                let status = status?;
-                if status.success() {
-                    break Ok(());
+        match exists {
+            // pull limit-server repo
+            true => command
+                .arg("-C")
+                .arg(repo)
+                .args(["pull", "--recurse-submodules", mirror]),
+            // clone limit-server repo
+            false => command.args(["clone", "--recursive", mirror]).arg(repo),
+        };
+
+        let proc = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let res = trace_process(proc, progress, 99, events, |e| {
+            tr!(
+                "Git exit with {}\n\n{}",
+                e.to_string(),
+                Help::Git.to_string()
+            )
+            .into()
+        })
+        .await;
+
+        match res {
+            Ok(_) => return Ok(mirror.clone()),
+            Err(e) => {
+                events.warn(tr!(
+                    "Mirror {} failed: {}",
+                    mirror.to_string(),
+                    e.to_string()
+                ));
+
+                // remove what the failed clone left behind
+                if !exists {
+                    std::fs::remove_dir_all(repo).ok();
                 }
 
-                return Err(on_failed(status));
+                last_error = Some(e);
             }
         }
     }
+
+    Err(last_error.unwrap_or_else(|| tr!("No mirror is configured").into()))
 }
 
-async fn install_elixir(
-    progress: &mut usize,
-    callback: Arc<impl Fn(usize, String, String) + Send + 'static>,
-) -> crate::Result<()> {
-    let proc = PackageManager::new()?.install(vec!["elixir"]).await?;
-
-    trace_process(proc, progress, 49, callback, |e| {
-        tr!(
-            "Package manager exit with {}\n\n{}",
-            e.to_string(),
-            Help::Network.to_string()
-        )
-        .into()
-    })
-    .await
+pub async fn update(_config: InstallConfig, events: Events) -> crate::Result<super::UpdateResult> {
+    events.finish(Err(tr!("Unsupported platform").into()))
 }
 
-async fn clone_or_pull_repo(
-    config: InstallConfig,
-    progress: &mut usize,
-    callback: Arc<impl Fn(usize, String, String) + Send + 'static>,
-) -> crate::Result<()> {
-    let mut command = Command::new("git");
-
-    match Path::new(&config.install_root)
-        .join("limit-server")
-        .exists()
-    {
-        // pull limit-server repo
-        true => command.args(&["pull", "--recurse-submodules"]),
-        // clone limit-server repo
-        false => command.args(&[
-            "clone",
-            "--recursive",
-            "https://github.com/Limit-LAB/limit-server",
-        ]),
-    };
-
-    let proc = command
-        .stdin(Stdio::null())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    trace_process(proc, progress, 99, callback, |e| {
-        tr!(
-            "Package manager exit with {}\n\n{}",
-            e.to_string(),
-            Help::Network.to_string()
-        )
-        .into()
-    })
-    .await
+pub async fn update_plan(
+    _config: &InstallConfig,
+) -> crate::Result<(super::UpdateResult, Vec<Action>)> {
+    Err(tr!("Unsupported platform").into())
 }
 
-pub async fn update() {}
+pub fn uninstall_plan(_config: &InstallConfig, _purge: bool) -> crate::Result<Vec<Action>> {
+    Err(tr!("Unsupported platform").into())
+}
 
-pub async fn uninstall() {}
+pub async fn uninstall(
+    _config: InstallConfig,
+    _plan: Vec<Action>,
+    events: Events,
+) -> crate::Result<()> {
+    events.finish(Err(tr!("Unsupported platform").into()))
+}
+
+pub async fn bundle(
+    _config: InstallConfig,
+    _output: Option<std::path::PathBuf>,
+    events: Events,
+) -> crate::Result<std::path::PathBuf> {
+    events.finish(Err(tr!("Unsupported platform").into()))
+}
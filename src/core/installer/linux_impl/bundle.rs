@@ -0,0 +1,194 @@
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use r18::tr;
+use reqwest::Url;
+
+use super::{
+    asset_name, download_actions, find_fuse, resolve_release, server_actions, Action, Events,
+    Executor, InstallConfig,
+};
+use crate::core::{
+    checksum::CHECKSUM_FILE,
+    http,
+    release::{Mirror, Release, Version, MIRROR_INDEX},
+    signature::SIGNATURE_SUFFIX,
+};
+
+/// Return the default file name of the bundle of a release
+pub fn bundle_name(release: &Release) -> String {
+    format!(
+        "limit-server-{}-{}-{}-{}.tar.gz",
+        release.tag_name,
+        env!("TARGET_ARCH"),
+        env!("TARGET_OS"),
+        env!("TARGET_ENV")
+    )
+}
+
+/// Download everything needed to install limit-server offline and pack it into a
+/// bundle, which is a gzipped tarball laid out as a mirror.
+///
+/// Returns the path of the bundle, it is named by `bundle_name` in the current
+/// directory if `output` is not given.
+pub async fn bundle(
+    config: InstallConfig,
+    output: Option<PathBuf>,
+    events: Events,
+) -> crate::Result<PathBuf> {
+    let res = pack_release(&config, output, &events).await;
+
+    events.finish(res)
+}
+
+// download the release and pack it into a bundle at `output`
+async fn pack_release(
+    config: &InstallConfig,
+    output: Option<PathBuf>,
+    events: &Events,
+) -> crate::Result<PathBuf> {
+    http::configure(&config.http)?;
+
+    let release = resolve_release(config, events).await?;
+    let output = output.unwrap_or_else(|| PathBuf::from(bundle_name(&release)));
+
+    // the staging directory is kept on failure so that the download can be resumed
+    let staging = env::temp_dir().join(format!("limit-up-bundle-{}", release.tag_name));
+    let release_dir = staging.join(&release.tag_name);
+    fs::create_dir_all(&release_dir)?;
+
+    let mut executor = Executor::new(config, events);
+    executor
+        .run(download_actions(
+            config,
+            &release,
+            &release_dir.join(asset_name()),
+        )?)
+        .await?;
+
+    let signature = format!("{}{}", asset_name(), SIGNATURE_SUFFIX);
+    fs::rename(
+        executor.verified(&asset_name())?.path,
+        release_dir.join(asset_name()),
+    )?;
+    fs::write(
+        release_dir.join(CHECKSUM_FILE),
+        &executor.texts[CHECKSUM_FILE],
+    )?;
+    fs::write(release_dir.join(&signature), &executor.texts[&signature])?;
+    fs::write(
+        staging.join(MIRROR_INDEX),
+        serde_json::to_string_pretty(&[&release])?,
+    )?;
+
+    if let Err(e) = pack(&staging, &output) {
+        fs::remove_file(&output).ok();
+        return Err(e);
+    }
+
+    fs::remove_dir_all(&staging)?;
+
+    Ok(output)
+}
+
+fn pack(dir: &Path, output: &Path) -> crate::Result<()> {
+    let mut tar = tar::Builder::new(GzEncoder::new(
+        File::create(output)?,
+        Compression::default(),
+    ));
+
+    tar.append_dir_all(".", dir)?;
+    tar.into_inner()?.finish()?.sync_all()?;
+
+    Ok(())
+}
+
+// where bundles are unpacked while installing
+fn unpack_dir() -> PathBuf {
+    env::temp_dir().join(format!("limit-up-bundle-{}", process::id()))
+}
+
+/// Unpack a bundle into `target`
+pub fn unpack(bundle: &Path, target: &Path) -> crate::Result<()> {
+    tar::Archive::new(GzDecoder::new(File::open(bundle)?)).unpack(target)?;
+
+    Ok(())
+}
+
+// read the release in a bundle without unpacking it
+fn read_release(bundle: &Path) -> crate::Result<Release> {
+    let invalid = || tr!("Invalid bundle {}", bundle.display().to_string()).to_string();
+
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(bundle)?));
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.path()?.ends_with(MIRROR_INDEX) {
+            continue;
+        }
+
+        return serde_json::from_reader::<_, Vec<Release>>(entry)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid().into());
+    }
+
+    Err(invalid().into())
+}
+
+// plan installing limit-server from a bundle, it is verified the same way as downloads
+pub(super) fn bundle_plan(config: &InstallConfig, path: &Path) -> crate::Result<Vec<Action>> {
+    let dir = unpack_dir();
+    let release = read_release(path)?;
+
+    // install the bundled release as if the bundle were a mirror
+    let config = InstallConfig {
+        mirrors: vec![Mirror::parse(
+            Url::from_directory_path(&dir)
+                .map_err(|_| tr!("Invalid bundle {}", path.display().to_string()).to_string())?
+                .as_str(),
+        )],
+        version: Version::Tag(release.tag_name.clone()),
+        bundle: None,
+        ..config.clone()
+    };
+    let extract = config.extract.unwrap_or_else(|| find_fuse().is_err());
+
+    let mut actions = vec![Action::Unpack {
+        bundle: path.to_path_buf(),
+        target: dir.clone(),
+    }];
+    actions.extend(server_actions(&config, &release, extract, Vec::new())?);
+
+    // the temporary mirror is gone once installed, record the bundle instead
+    if let Some(Action::WriteReceipt { receipt, .. }) = actions.last_mut() {
+        receipt.source = fs::canonicalize(path)?.display().to_string();
+    }
+
+    actions.push(Action::Remove(dir));
+
+    Ok(actions)
+}
+
+// install limit-server from a bundle
+pub(super) async fn install_bundle(
+    config: &InstallConfig,
+    path: &Path,
+    events: &Events,
+) -> crate::Result<()> {
+    let res = match bundle_plan(config, path) {
+        Ok(actions) => Executor::new(config, events).run(actions).await,
+        Err(e) => Err(e),
+    };
+
+    // the bundle is unpacked again by the next attempt
+    if res.is_err() {
+        fs::remove_dir_all(unpack_dir()).ok();
+    }
+
+    res
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs,
+    iter::empty,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use r18::tr;
+use tokio::process::Command;
+
+use super::find_command;
+
+/// Directory the AppImage is extracted into
+pub const EXTRACT_DIR: &str = "limit-server";
+
+/// Script running limit-server however it is installed, so that services
+/// don't need to change when the install mode does
+pub const LAUNCHER: &str = "limit-server.sh";
+
+const LAUNCHER_SCRIPT: &str = r#"#!/bin/sh
+# Generated by limit-up, runs limit-server
+exec "$(dirname "$(readlink -f "$0")")/limit-server.Appimage" "$@"
+"#;
+
+// runs AppRun of the extracted AppImage the way the AppImage runtime would
+const EXTRACTED_LAUNCHER_SCRIPT: &str = r#"#!/bin/sh
+# Generated by limit-up, runs limit-server extracted from its AppImage
+APPDIR="$(dirname "$(readlink -f "$0")")/limit-server"
+export APPDIR
+exec "$APPDIR/AppRun" "$@"
+"#;
+
+/// Return fusermount if AppImages can be mounted, otherwise why they can't
+pub fn find_fuse() -> Result<PathBuf, String> {
+    if !Path::new("/dev/fuse").exists() {
+        return Err(tr!("/dev/fuse does not exist").to_string());
+    }
+
+    ["fusermount3", "fusermount"]
+        .iter()
+        .find_map(|command| find_command(command, empty::<&str>()).into_iter().next())
+        .ok_or_else(|| tr!("fusermount is not found in PATH").to_string())
+}
+
+/// Return the launcher script, for the extracted AppImage if `extracted`
+pub fn launcher_script(extracted: bool) -> &'static str {
+    match extracted {
+        true => EXTRACTED_LAUNCHER_SCRIPT,
+        false => LAUNCHER_SCRIPT,
+    }
+}
+
+/// Extract `appimage` into `target`,
+/// the previously extracted AppImage is only replaced once extracting succeeds
+pub async fn extract_appimage(appimage: &Path, target: &Path) -> crate::Result<()> {
+    let appimage = fs::canonicalize(appimage)?;
+
+    // the AppImage runtime always extracts into `squashfs-root` of the working directory
+    let mut temp = target.as_os_str().to_owned();
+    temp.push(".extracting");
+    let temp = PathBuf::from(temp);
+    if temp.exists() {
+        fs::remove_dir_all(&temp)?;
+    }
+    fs::create_dir_all(&temp)?;
+
+    let output = Command::new(&appimage)
+        .arg("--appimage-extract")
+        .current_dir(&temp)
+        .stdin(Stdio::null())
+        .output()
+        .await;
+
+    let extracted = temp.join("squashfs-root");
+    let res = match output {
+        Ok(output) if output.status.success() && extracted.join("AppRun").exists() => Ok(()),
+        Ok(output) => Err(tr!(
+            "Failed to extract {}: {}",
+            appimage.display().to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string()
+        )
+        .into()),
+        Err(e) => Err(tr!(
+            "Failed to extract {}: {}",
+            appimage.display().to_string(),
+            e.to_string()
+        )
+        .into()),
+    };
+
+    if let Err(e) = res {
+        fs::remove_dir_all(&temp).ok();
+        return Err(e);
+    }
+
+    if target.exists() {
+        fs::remove_dir_all(target)?;
+    }
+
+    fs::rename(&extracted, target)?;
+    fs::remove_dir_all(&temp)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix::prelude::PermissionsExt, process::Command};
+
+    use super::{extract_appimage, launcher_script, EXTRACT_DIR, LAUNCHER};
+    use crate::core::RT;
+
+    // a fake AppImage that extracts an AppRun printing its arguments
+    const FAKE_APPIMAGE: &str = r#"#!/bin/sh
+[ "$1" = "--appimage-extract" ] || exit 1
+mkdir -p squashfs-root
+printf '#!/bin/sh\necho "$APPDIR" "$@"\n' > squashfs-root/AppRun
+chmod +x squashfs-root/AppRun
+"#;
+
+    #[test]
+    fn test_extract_appimage() {
+        let dir = env::temp_dir().join("limit-up-extract-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        let appimage = dir.join("fake.AppImage");
+        fs::write(&appimage, FAKE_APPIMAGE).unwrap();
+        fs::set_permissions(&appimage, fs::Permissions::from_mode(0o755)).unwrap();
+
+        RT.block_on(extract_appimage(&appimage, &dir.join(EXTRACT_DIR)))
+            .unwrap();
+        fs::write(dir.join(LAUNCHER), launcher_script(true)).unwrap();
+        fs::set_permissions(dir.join(LAUNCHER), fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(!dir.join(format!("{}.extracting", EXTRACT_DIR)).exists());
+
+        let output = Command::new(dir.join(LAUNCHER))
+            .arg("start")
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            format!("{} start", dir.join(EXTRACT_DIR).display())
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,51 +1,1065 @@
+mod bundle;
+mod extract;
+
+pub use bundle::bundle;
+
+use std::collections::HashMap;
 use std::env;
-use std::fs::File;
-use std::io::Write;
+use std::fmt;
+use std::fs::{self, Permissions};
+use std::future::Future;
+use std::io;
+use std::ops::Range;
 use std::os::unix::prelude::PermissionsExt;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
-use crate::core::HTTP_CLIENT;
+use r18::tr;
 
-pub async fn install(
-    config: super::InstallConfig,
-    callback: impl Fn(usize) + Send + 'static,
-) -> crate::Result<()> {
-    let mut target = File::create(Path::new(&config.install_root).join("limit-server.Appimage"))?;
-
-    let mut permission = target.metadata()?.permissions();
-    permission.set_mode(0o755);
-    target.set_permissions(permission)?;
-
-    let mut resp = HTTP_CLIENT
-        .get(format!(
-            "https://github.com/Limit-LAB/limit-server/releases/latest/download/limit_up-{}-{}-{}",
-            env!("TARGET_ARCH"),
-            env!("TARGET_OS"),
-            env!("TARGET_ENV")
-        ))
-        .send()
-        .await?;
+use super::{
+    doctor::{self, Check, Level},
+    find_command,
+    pkgmanager::{self, PackageManager},
+    privilege::Privilege,
+    Action, Command, Events, InstallConfig, InstallEvent, Receipt, UpdateResult,
+};
+use crate::core::{
+    checksum::{self, CHECKSUM_FILE},
+    config,
+    download::{self, fetch_text},
+    helper::Help,
+    http,
+    release::{self, Release, Version},
+    service,
+    signature::{self, SIGNATURE_SUFFIX},
+    tls,
+};
+use extract::{extract_appimage, find_fuse, launcher_script, EXTRACT_DIR, LAUNCHER};
+
+const SERVER_BINARY: &str = "limit-server.Appimage";
+
+/// Commands limit-server installed as `receipt` says needs to run
+pub fn required_commands(receipt: Option<&Receipt>) -> Vec<Command> {
+    // services run limit-server through the launcher, which is a shell script
+    let mut commands = vec![Command::find("sh")];
+
+    // the AppImage is mounted with FUSE every time it runs, unless it is extracted
+    let extracted = receipt
+        .and_then(|receipt| receipt.files.first())
+        .is_some_and(|file| file == Path::new(EXTRACT_DIR));
+
+    if !extracted {
+        commands.push(Command {
+            name: "fusermount".to_string(),
+            path: find_fuse().ok(),
+        });
+    }
+
+    commands
+}
+
+// files and directories holding user data, they are kept unless purging
+const USER_DATA: &[&str] = &[
+    config::CONFIG_FILE,
+    "data",
+    service::supervisor::LOG_DIR,
+    tls::TLS_DIR,
+];
+
+// size of the AppImage with some headroom
+const REQUIRED_SPACE: u64 = 200 * 1024 * 1024;
+
+// tools the installer runs, the launcher is a shell script
+const TOOLS: &[&str] = &["sh"];
 
-    let total = resp
-        .content_length()
-        .ok_or("Unknown size when downloading Appimage".to_string())?;
-    let mut current = 0;
-    let mut old_progress = 0;
+/// Check whether limit-server can be installed and run
+pub async fn doctor(config: &InstallConfig) -> crate::Result<Vec<Check>> {
+    http::configure(&config.http)?;
 
-    while let Some(chunk) = resp.chunk().await? {
-        target.write_all(&chunk)?;
+    let mut checks = vec![
+        doctor::check_install_root(config),
+        doctor::check_disk_space(config, REQUIRED_SPACE),
+    ];
 
-        current += chunk.len() as u64;
-        let new_progress = (current as f64 / total as f64 * 100.0) as usize;
-        if new_progress != old_progress {
-            callback(new_progress);
-            old_progress = new_progress;
+    // nothing is downloaded when installing from a bundle
+    if config.bundle.is_none() {
+        let mirrors = config
+            .mirrors
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<_>>();
+        checks.push(doctor::check_network(&mirrors).await);
+    }
+
+    checks.extend(doctor::check_tools(TOOLS));
+    checks.push(check_fuse(config));
+    checks.push(check_root(config));
+
+    Ok(checks)
+}
+
+// Root is needed to install FUSE with the package manager,
+// failing that is not fatal since the AppImage is extracted then
+fn check_root(config: &InstallConfig) -> Check {
+    let name = tr!("Privileges");
+
+    if !config.install_dependencies || config.extract.is_some() || find_fuse().is_ok() {
+        return Check::new(
+            name,
+            Level::Pass,
+            tr!("No packages to install, Root is not required"),
+            None,
+        );
+    }
+
+    match Privilege::detect(config.elevation.clone()) {
+        Ok(privilege) => match privilege.tool() {
+            None => Check::new(name, Level::Pass, tr!("Running as Root"), None),
+            Some(tool) => Check::new(
+                name,
+                Level::Pass,
+                tr!("Commands that need Root will be run with {}", tool),
+                None,
+            ),
+        },
+        Err(_) => Check::new(
+            name,
+            Level::Warn,
+            tr!("Root is required to install FUSE, but none of sudo, doas and pkexec is found"),
+            Some(Help::Root),
+        ),
+    }
+}
+
+// AppImages are mounted with FUSE when they run, unless they are extracted
+fn check_fuse(config: &InstallConfig) -> Check {
+    let name = tr!("FUSE");
+
+    match (find_fuse(), config.extract) {
+        (_, Some(true)) => Check::new(
+            name,
+            Level::Pass,
+            tr!("Not required, the AppImage will be extracted"),
+            None,
+        ),
+        (Ok(fusermount), _) => Check::new(name, Level::Pass, fusermount.display(), None),
+        (Err(reason), Some(false)) => Check::new(name, Level::Warn, reason, Some(Help::Fuse)),
+        (Err(reason), None) => Check::new(
+            name,
+            Level::Pass,
+            tr!("{}, the AppImage will be extracted", reason),
+            None,
+        ),
+    }
+}
+
+pub async fn install(config: InstallConfig, events: Events) -> crate::Result<()> {
+    let res = async {
+        doctor::preflight(doctor(&config).await?, &events)?;
+
+        if let Some(path) = &config.bundle {
+            return bundle::install_bundle(&config, path, &events).await;
+        }
+
+        // AppImages can be mounted once FUSE is installed
+        let mut dependencies = Vec::new();
+        if install_fuse(&config, &events).await {
+            dependencies.push("fuse".to_string());
         }
+
+        let release = resolve_release(&config, &events).await?;
+        let extract = config.extract.unwrap_or_else(|| find_fuse().is_err());
+        let actions = server_actions(&config, &release, extract, dependencies)?;
+
+        Executor::new(&config, &events).run(actions).await
+    }
+    .await;
+
+    events.finish(res)
+}
+
+/// Plan what `install` would do without changing anything
+pub async fn install_plan(config: &InstallConfig) -> crate::Result<Vec<Action>> {
+    http::configure(&config.http)?;
+
+    if let Some(path) = &config.bundle {
+        return bundle::bundle_plan(config, path);
+    }
+
+    // FUSE is expected to be installed as planned, it is not if it can't be
+    let mut actions = fuse_actions(config).unwrap_or_default();
+    let fuse = !actions.is_empty();
+
+    let release = resolve_release(config, &Events::discard()).await?;
+    let extract = config
+        .extract
+        .unwrap_or_else(|| !fuse && find_fuse().is_err());
+    let dependencies = match fuse {
+        true => vec!["fuse".to_string()],
+        false => Vec::new(),
+    };
+
+    actions.extend(server_actions(config, &release, extract, dependencies)?);
+
+    Ok(actions)
+}
+
+// plan installing FUSE with the package manager if it is wanted and missing
+fn fuse_actions(config: &InstallConfig) -> crate::Result<Vec<Action>> {
+    if !config.install_dependencies || config.extract.is_some() || find_fuse().is_ok() {
+        return Ok(Vec::new());
+    }
+
+    // fail early if there is no way to run the package manager as Root
+    Privilege::detect(config.elevation.clone())?;
+
+    let mgr = PackageManager::new()?;
+    let packages = mgr.packages("fuse");
+
+    Ok(vec![Action::Packages {
+        manager: mgr.name(),
+        steps: mgr.install_steps(&packages)?,
+        packages,
+    }])
+}
+
+// install FUSE with the package manager if it is wanted and missing, returns whether it was installed,
+// failing is not fatal since the AppImage is extracted without FUSE
+async fn install_fuse(config: &InstallConfig, events: &Events) -> bool {
+    let res: crate::Result<bool> = async {
+        let actions = fuse_actions(config)?;
+        if actions.is_empty() {
+            return Ok(false);
+        }
+
+        Executor::new(config, events).run(actions).await?;
+        find_fuse()?;
+
+        Ok(true)
+    }
+    .await;
+
+    res.unwrap_or_else(|e| {
+        events.warn(tr!(
+            "Unable to install FUSE, the AppImage will be extracted: {}",
+            e.to_string()
+        ));
+
+        false
+    })
+}
+
+// find the release to install
+async fn resolve_release(config: &InstallConfig, events: &Events) -> crate::Result<Release> {
+    with_mirrors(config, &config.mirrors, events, |mirror| {
+        let mirror = mirror.clone();
+        let version = config.version.clone();
+
+        async move { mirror.resolve(&version).await }
+    })
+    .await
+}
+
+// run a network operation against the mirrors in order until one of them succeeds,
+// every mirror is retried with the retry policy of the config,
+// retries and failed mirrors are reported as warnings
+async fn with_mirrors<M: fmt::Display, T, Fut>(
+    config: &InstallConfig,
+    mirrors: &[M],
+    events: &Events,
+    mut f: impl FnMut(&M) -> Fut,
+) -> crate::Result<T>
+where
+    Fut: Future<Output = crate::Result<T>>,
+{
+    let mut last_error = None;
+
+    for mirror in mirrors {
+        let res = config
+            .retry
+            .run(
+                || f(mirror),
+                |attempt, attempts, e| {
+                    events.warn(tr!(
+                        "{}, retrying ({}/{})...",
+                        e.to_string(),
+                        (attempt + 1).to_string(),
+                        attempts.to_string()
+                    ));
+                },
+            )
+            .await;
+
+        match res {
+            Ok(res) => return Ok(res),
+            Err(e) => {
+                events.warn(tr!(
+                    "Mirror {} failed: {}",
+                    mirror.to_string(),
+                    e.to_string()
+                ));
+
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| tr!("No mirror is configured").into()))
+}
+
+// name of the release asset for the current platform
+fn asset_name() -> String {
+    format!(
+        "limit_up-{}-{}-{}",
+        env!("TARGET_ARCH"),
+        env!("TARGET_OS"),
+        env!("TARGET_ENV")
+    )
+}
+
+// plan downloading the asset of the release into `target` and verifying it
+fn download_actions(
+    config: &InstallConfig,
+    release: &Release,
+    target: &Path,
+) -> crate::Result<Vec<Action>> {
+    // fail before downloading anything if the key is invalid
+    signature::load_public_key(config.trusted_key.as_deref())?;
+
+    let urls = |name: &str| {
+        config
+            .mirrors
+            .iter()
+            .map(|mirror| mirror.asset_url(release, name))
+            .collect()
+    };
+    let signature = format!("{}{}", asset_name(), SIGNATURE_SUFFIX);
+
+    Ok(vec![
+        Action::Download {
+            name: CHECKSUM_FILE.to_string(),
+            urls: urls(CHECKSUM_FILE),
+            target: None,
+        },
+        Action::Download {
+            name: signature.clone(),
+            urls: urls(&signature),
+            target: None,
+        },
+        Action::Download {
+            name: asset_name(),
+            urls: urls(&asset_name()),
+            target: Some(target.to_path_buf()),
+        },
+        Action::Verify {
+            name: asset_name(),
+            checksums: CHECKSUM_FILE.to_string(),
+            signature,
+        },
+    ])
+}
+
+// plan installing limit-server of the given release into the install root,
+// the installed binary is only replaced once the download has been verified
+fn server_actions(
+    config: &InstallConfig,
+    release: &Release,
+    extract: bool,
+    dependencies: Vec<String>,
+) -> crate::Result<Vec<Action>> {
+    let root = Path::new(&config.install_root);
+    let mut actions = Vec::new();
+
+    if !root.exists() {
+        actions.push(Action::CreateDir(root.to_path_buf()));
+    }
+
+    actions.extend(download_actions(
+        config,
+        release,
+        &root.join(SERVER_BINARY),
+    )?);
+
+    // the source is known once downloaded
+    let mut receipt = Receipt::new(&release.tag_name, "");
+    receipt.published_at = release.published_at.clone();
+
+    // AppImages can't be mounted without FUSE, run the extracted payload instead
+    match extract {
+        true => {
+            actions.push(Action::Extract {
+                name: asset_name(),
+                target: root.join(EXTRACT_DIR),
+            });
+            receipt.files = vec![EXTRACT_DIR.into(), LAUNCHER.into()];
+        }
+        false => {
+            actions.push(Action::Install {
+                name: asset_name(),
+                path: root.join(SERVER_BINARY),
+                mode: 0o755,
+            });
+            receipt.files = vec![SERVER_BINARY.into(), LAUNCHER.into()];
+        }
+    }
+
+    actions.push(Action::WriteFile {
+        path: root.join(LAUNCHER),
+        mode: 0o755,
+        contents: launcher_script(extract).to_string(),
+    });
+    receipt.command = Some(LAUNCHER.into());
+
+    if let Some(old) = Receipt::load(root)? {
+        // remove what the previous install left behind if the mode has changed
+        actions.extend(
+            old.files
+                .iter()
+                .filter(|file| !receipt.files.contains(file))
+                .map(|file| root.join(file))
+                .filter(|path| path.symlink_metadata().is_ok())
+                .map(Action::Remove),
+        );
+
+        // services keep working since they run the launcher
+        receipt.services = old.services;
+        receipt.dependencies = old.dependencies;
+    }
+
+    for dependency in dependencies {
+        if !receipt.dependencies.contains(&dependency) {
+            receipt.dependencies.push(dependency);
+        }
+    }
+
+    actions.push(Action::WriteReceipt {
+        root: root.to_path_buf(),
+        receipt: Box::new(receipt),
+    });
+
+    Ok(actions)
+}
+
+// a downloaded file
+struct Download {
+    path: PathBuf,
+    // URL it was downloaded from
+    source: String,
+    // set once its checksum and signature are verified
+    sha256: Option<String>,
+}
+
+// executes planned actions, what they produce is passed on to the following ones,
+// every action is a step of the events sent
+struct Executor<'a> {
+    config: &'a InstallConfig,
+    events: Events,
+    // progress in percent the running action spans
+    progress: Range<usize>,
+    // files downloaded into memory by name
+    texts: HashMap<String, String>,
+    // files downloaded to disk by name, until they are installed
+    downloads: HashMap<String, Download>,
+    // where the installed release came from and the checksum of what was installed
+    source: Option<String>,
+    sha256: Option<String>,
+}
+
+impl<'a> Executor<'a> {
+    fn new(config: &'a InstallConfig, events: &Events) -> Self {
+        Self {
+            config,
+            events: events.clone(),
+            progress: 0..0,
+            texts: HashMap::new(),
+            downloads: HashMap::new(),
+            source: None,
+            sha256: None,
+        }
+    }
+
+    // execute actions in order until one of them fails
+    async fn run(&mut self, actions: Vec<Action>) -> crate::Result<()> {
+        let total = actions.len();
+
+        for (i, action) in actions.into_iter().enumerate() {
+            let step = action.to_string();
+            self.progress = i * 100 / total..(i + 1) * 100 / total;
+
+            self.events.send(InstallEvent::StepStarted(step.clone()));
+            self.events.progress(self.progress.start);
+            self.execute(action).await?;
+            self.events.send(InstallEvent::StepFinished(step));
+        }
+
+        self.events.progress(100);
+
+        Ok(())
+    }
+
+    async fn execute(&mut self, action: Action) -> crate::Result<()> {
+        match action {
+            Action::Packages { steps, .. } => {
+                let privilege = Privilege::detect(self.config.elevation.clone())?;
+                pkgmanager::run(&privilege, &steps, self.progress.clone(), &self.events).await?;
+            }
+            Action::Download {
+                name,
+                urls,
+                target: None,
+            } => {
+                let text = with_mirrors(self.config, &urls, &self.events, |url| {
+                    let url = url.clone();
+                    async move { fetch_text(&url).await }
+                })
+                .await?;
+
+                self.texts.insert(name, text);
+            }
+            Action::Download {
+                name,
+                urls,
+                target: Some(target),
+            } => {
+                let (progress, events) = (self.progress.clone(), self.events.clone());
+
+                // an interrupted download is kept so that it can be resumed by the next attempt
+                let (path, source) = with_mirrors(self.config, &urls, &self.events, |url| {
+                    let url = url.clone();
+                    let target = target.clone();
+                    let progress = progress.clone();
+                    let events = events.clone();
+
+                    async move {
+                        download::download(&url, target, move |p| {
+                            events.progress(progress.start + progress.len() * p / 100);
+                        })
+                        .await
+                        .map(|path| (path, url))
+                    }
+                })
+                .await?;
+
+                self.downloads.insert(
+                    name,
+                    Download {
+                        path,
+                        source,
+                        sha256: None,
+                    },
+                );
+            }
+            Action::Verify {
+                name,
+                checksums,
+                signature,
+            } => {
+                let text = |name: &str| {
+                    self.texts
+                        .get(name)
+                        .ok_or_else(|| tr!("{} has not been downloaded", name).to_string())
+                };
+                let (sums, signature) = (text(&checksums)?, text(&signature)?);
+
+                let download = self
+                    .downloads
+                    .get_mut(&name)
+                    .ok_or_else(|| tr!("{} has not been downloaded", &name).to_string())?;
+
+                download.sha256 =
+                    Some(verify(self.config, &name, &download.path, sums, signature)?);
+            }
+            Action::Install { name, path, mode } => {
+                let download = self.verified(&name)?;
+
+                fs::set_permissions(&download.path, Permissions::from_mode(mode))?;
+                fs::rename(&download.path, path)?;
+
+                self.source = Some(download.source);
+                self.sha256 = download.sha256;
+            }
+            Action::Extract { name, target } => {
+                let download = self.verified(&name)?;
+
+                extract_appimage(&download.path, &target).await?;
+                fs::remove_file(&download.path)?;
+
+                // the AppImage is gone, status verifies what it was extracted into
+                self.source = Some(download.source);
+                self.sha256 = Some(checksum::sha256_path(&target)?);
+            }
+            Action::Unpack { bundle, target } => bundle::unpack(&bundle, &target)?,
+            Action::CreateDir(path) => fs::create_dir_all(path)?,
+            Action::WriteFile {
+                path,
+                mode,
+                contents,
+            } => {
+                fs::write(&path, contents)?;
+                fs::set_permissions(&path, Permissions::from_mode(mode))?;
+            }
+            Action::WriteReceipt { root, mut receipt } => {
+                if receipt.source.is_empty() {
+                    receipt.source = self.source.take().unwrap_or_default();
+                }
+                receipt.sha256 = self.sha256.take();
+
+                receipt.save(root)?;
+            }
+            Action::StopServer(root) => {
+                service::supervisor::stop(&root).await?;
+            }
+            Action::RemoveService(path) => service::remove_service(self.config, &path).await?,
+            Action::Remove(path) => remove(&path)?,
+            action @ (Action::GitClone { .. } | Action::GitPull { .. }) => {
+                Err(tr!("{} is not supported on this platform", action.to_string()).to_string())?
+            }
+        }
+
+        Ok(())
+    }
+
+    // take a download that has been verified
+    fn verified(&mut self, name: &str) -> crate::Result<Download> {
+        self.downloads
+            .remove(name)
+            .filter(|download| download.sha256.is_some())
+            .ok_or_else(|| tr!("{} has not been verified", name).into())
+    }
+}
+
+// verify a download with the checksum file and the signature of the release,
+// it is removed if it doesn't match, returns its checksum
+fn verify(
+    config: &InstallConfig,
+    name: &str,
+    path: &Path,
+    sums: &str,
+    signature: &str,
+) -> crate::Result<String> {
+    let trusted_key = signature::load_public_key(config.trusted_key.as_deref())?;
+    let expected = expected_checksum(sums, name)?;
+
+    let actual = checksum::sha256_file(path)?;
+    if actual != expected {
+        fs::remove_file(path)?;
+
+        Err(tr!(
+            "Checksum mismatch for {}, expected {} but got {}\n\n{}",
+            name.to_string(),
+            expected,
+            actual.clone(),
+            Help::Checksum.to_string()
+        )
+        .to_string())?;
+    }
+
+    if let Err(e) = signature::verify_file(path, signature, &trusted_key) {
+        fs::remove_file(path)?;
+
+        Err(tr!(
+            "Signature verification of {} failed: {}\n\n{}",
+            name.to_string(),
+            e.to_string(),
+            Help::Signature.to_string()
+        )
+        .to_string())?;
+    }
+
+    Ok(actual)
+}
+
+// find the checksum of a file in the checksum file of the release
+fn expected_checksum(sums: &str, name: &str) -> crate::Result<String> {
+    checksum::find_checksum(sums, name).ok_or_else(|| {
+        tr!(
+            "No checksum of {} found in {}\n\n{}",
+            name,
+            CHECKSUM_FILE,
+            &Help::Checksum.to_string()
+        )
+        .into()
+    })
+}
+
+// remove a file or a directory, it is fine if it is gone already
+fn remove(path: &Path) -> crate::Result<()> {
+    match path.symlink_metadata() {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path)?,
+        Ok(_) => fs::remove_file(path)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => Err(e)?,
     }
 
     Ok(())
 }
 
-pub async fn update() {}
+pub async fn update(config: InstallConfig, events: Events) -> crate::Result<UpdateResult> {
+    let res = async {
+        let (result, actions) = plan_update(&config, &events).await?;
+
+        Executor::new(&config, &events).run(actions).await?;
+
+        Ok(result)
+    }
+    .await;
+
+    events.finish(res)
+}
+
+/// Plan what `update` would do without changing anything,
+/// nothing is planned if limit-server is up to date
+pub async fn update_plan(config: &InstallConfig) -> crate::Result<(UpdateResult, Vec<Action>)> {
+    plan_update(config, &Events::discard()).await
+}
+
+// check for updates and plan updating to the latest release
+async fn plan_update(
+    config: &InstallConfig,
+    events: &Events,
+) -> crate::Result<(UpdateResult, Vec<Action>)> {
+    http::configure(&config.http)?;
+
+    let receipt = Receipt::load(&config.install_root)?;
+    if receipt.is_none() && !Path::new(&config.install_root).join(SERVER_BINARY).exists() {
+        Err(tr!("limit-server is not installed in {}", &config.install_root).to_string())?;
+    }
+
+    let latest = resolve_release(config, events).await?;
+
+    // a pinned version is installed even if it is older than the installed one
+    let up_to_date = |installed: &Receipt| match config.version {
+        Version::Latest(_) => !release::is_newer(
+            &latest,
+            &installed.version,
+            installed.published_at.as_deref(),
+        ),
+        Version::Tag(_) => latest.tag_name == installed.version,
+    };
+
+    match receipt {
+        Some(receipt) if up_to_date(&receipt) => {
+            Ok((UpdateResult::UpToDate(receipt.version), Vec::new()))
+        }
+        receipt => {
+            let installed = receipt.map(|receipt| receipt.version);
+            let extract = config.extract.unwrap_or_else(|| find_fuse().is_err());
+            let actions = server_actions(config, &latest, extract, Vec::new())?;
+
+            Ok((
+                UpdateResult::Updated {
+                    from: installed,
+                    to: latest.tag_name,
+                },
+                actions,
+            ))
+        }
+    }
+}
+
+/// Plan what `uninstall` is going to remove, nothing is planned if limit-server is not installed
+pub fn uninstall_plan(config: &InstallConfig, purge: bool) -> crate::Result<Vec<Action>> {
+    let root = Path::new(&config.install_root);
+
+    // remove what the receipt says was installed, or what we would install otherwise
+    let (files, services) = match Receipt::load(root)? {
+        Some(receipt) => (receipt.files, receipt.services),
+        None => (vec![SERVER_BINARY.into()], Vec::new()),
+    };
+
+    // uninstall often runs as Root, a tampered or corrupt receipt must not make it
+    // remove anything limit-up doesn't install
+    let foreign = files
+        .iter()
+        .find(|file| {
+            file.as_os_str().is_empty()
+                || file
+                    .components()
+                    .any(|c| !matches!(c, Component::Normal(_)))
+        })
+        .or_else(|| {
+            services.iter().find(|service| {
+                service::systemd::scope_of(service).is_none()
+                    && !service::openrc::is_script(service)
+            })
+        });
+
+    if let Some(path) = foreign {
+        Err(tr!(
+            "The receipt in {} lists {}, which is not installed by limit-up, it may be corrupt",
+            &config.install_root,
+            &path.display().to_string()
+        )
+        .to_string())?;
+    }
+
+    let exists = |path: &PathBuf| path.symlink_metadata().is_ok();
+
+    // services go first so that limit-server is stopped before its files are removed
+    let mut plan = services
+        .into_iter()
+        .filter(exists)
+        .map(Action::RemoveService)
+        .chain(
+            files
+                .iter()
+                .map(|file| root.join(file))
+                .chain(
+                    if purge { USER_DATA } else { &[] }
+                        .iter()
+                        .map(|name| root.join(name)),
+                )
+                .chain(download::leftovers(root.join(SERVER_BINARY)))
+                .chain([root.join(format!("{}.extracting", EXTRACT_DIR))])
+                .filter(exists)
+                .map(Action::Remove),
+        )
+        .collect::<Vec<_>>();
+
+    // limit-server may be running under the supervisor of limit-up
+    if service::supervisor::running(root).is_some() {
+        plan.insert(0, Action::StopServer(root.to_path_buf()));
+    }
+
+    // the receipt goes last so that an interrupted uninstall can be retried
+    let receipt = Receipt::path(root);
+    if receipt.exists() {
+        plan.push(Action::Remove(receipt));
+    }
+
+    Ok(plan)
+}
+
+pub async fn uninstall(
+    config: InstallConfig,
+    plan: Vec<Action>,
+    events: Events,
+) -> crate::Result<()> {
+    let res = async {
+        Executor::new(&config, &events).run(plan).await?;
+
+        // remove the install root as well if nothing is left
+        if fs::read_dir(&config.install_root).is_ok_and(|mut dir| dir.next().is_none()) {
+            fs::remove_dir(&config.install_root)?;
+        }
+
+        Ok(())
+    }
+    .await;
+
+    events.finish(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix::prelude::PermissionsExt, path::Path};
+
+    use super::{asset_name, LAUNCHER, SERVER_BINARY};
+    use crate::core::{
+        checksum::CHECKSUM_FILE,
+        installer::{Events, InstallConfig, Receipt, UpdateResult, RECEIPT_FILE},
+        release::{Mirror, MIRROR_INDEX},
+        signature::{
+            tests::{TEST_PUBLIC_KEY, TEST_SIGNATURE},
+            SIGNATURE_SUFFIX,
+        },
+        RT,
+    };
+
+    // lay out a mirror with a release whose asset is "limit"
+    fn create_mirror(dir: &Path) {
+        let release = dir.join("v0.1.0");
+        fs::create_dir_all(&release).unwrap();
+
+        fs::write(dir.join(MIRROR_INDEX), r#"[{ "tag_name": "v0.1.0" }]"#).unwrap();
+        fs::write(release.join(asset_name()), "limit").unwrap();
+        fs::write(
+            release.join(CHECKSUM_FILE),
+            format!(
+                "55ea09e5715d0a8d9d94018d473bf23b2d7e630c2adb1f1acad3bada74c6fd05  {}\n",
+                asset_name()
+            ),
+        )
+        .unwrap();
+        fs::write(
+            release.join(format!("{}{}", asset_name(), SIGNATURE_SUFFIX)),
+            TEST_SIGNATURE,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_install_from_mirror() {
+        let dir = env::temp_dir().join("limit-up-mirror-test");
+        fs::remove_dir_all(&dir).ok();
+        create_mirror(&dir.join("mirror"));
+
+        let mut config = InstallConfig {
+            install_root: dir.join("root").display().to_string(),
+            trusted_key: Some(TEST_PUBLIC_KEY.to_string()),
+            // the first mirror doesn't exist, we should fall through to the second one
+            mirrors: vec![
+                Mirror::parse(&format!("file://{}/missing", dir.display())),
+                Mirror::parse(&format!("file://{}/mirror", dir.display())),
+            ],
+            extract: Some(false),
+            ..Default::default()
+        };
+        config.retry.attempts = 1;
+
+        RT.block_on(super::install(config.clone(), Events::discard()))
+            .unwrap();
+
+        let binary = dir.join("root").join(SERVER_BINARY);
+        assert_eq!(fs::read_to_string(&binary).unwrap(), "limit");
+        assert_eq!(
+            binary.metadata().unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+
+        let receipt = Receipt::load(dir.join("root")).unwrap().unwrap();
+        assert_eq!(receipt.version, "v0.1.0");
+        assert_eq!(
+            receipt.source,
+            format!("file://{}/mirror/v0.1.0/{}", dir.display(), asset_name())
+        );
+        assert_eq!(
+            receipt.sha256.as_deref(),
+            Some("55ea09e5715d0a8d9d94018d473bf23b2d7e630c2adb1f1acad3bada74c6fd05")
+        );
+        assert_eq!(
+            receipt.files,
+            [Path::new(SERVER_BINARY), Path::new(LAUNCHER)]
+        );
+
+        assert!(matches!(
+            RT.block_on(super::update(config.clone(), Events::discard())),
+            Ok(UpdateResult::UpToDate(version)) if version == "v0.1.0"
+        ));
+
+        let plan = super::uninstall_plan(&config, false).unwrap();
+        assert_eq!(plan.len(), 3);
+        RT.block_on(super::uninstall(config, plan, Events::discard()))
+            .unwrap();
+        assert!(!dir.join("root").exists());
 
-pub async fn uninstall() {}
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_uninstall_tampered_receipt() {
+        let dir = env::temp_dir().join("limit-up-tampered-receipt-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        let config = InstallConfig {
+            install_root: dir.display().to_string(),
+            ..Default::default()
+        };
+
+        let mut receipt = Receipt::new("v0.1.0", "");
+        for file in ["../etc", "/etc", "data/../../etc"] {
+            receipt.files = vec![file.into()];
+            receipt.save(&dir).unwrap();
+            assert!(super::uninstall_plan(&config, false).is_err());
+        }
+
+        receipt.files = vec![SERVER_BINARY.into()];
+        receipt.services = vec!["/etc/passwd".into()];
+        receipt.save(&dir).unwrap();
+        assert!(super::uninstall_plan(&config, false).is_err());
+
+        receipt.services = vec!["/etc/init.d/limit-server".into()];
+        receipt.save(&dir).unwrap();
+        assert!(super::uninstall_plan(&config, false).is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_plan() {
+        let dir = env::temp_dir().join("limit-up-plan-test");
+        fs::remove_dir_all(&dir).ok();
+        create_mirror(&dir.join("mirror"));
+
+        let root = dir.join("root");
+        let config = InstallConfig {
+            install_root: root.display().to_string(),
+            trusted_key: Some(TEST_PUBLIC_KEY.to_string()),
+            mirrors: vec![Mirror::parse(&format!("file://{}/mirror", dir.display()))],
+            extract: Some(false),
+            ..Default::default()
+        };
+
+        let plan = RT.block_on(super::install_plan(&config)).unwrap();
+
+        let url = |name: &str| format!("file://{}/mirror/v0.1.0/{}", dir.display(), name);
+        let signature = format!("{}{}", asset_name(), SIGNATURE_SUFFIX);
+        let binary = root.join(SERVER_BINARY).display().to_string();
+        assert_eq!(
+            plan.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                format!("Create directory {}", root.display()),
+                format!("Download {}", url(CHECKSUM_FILE)),
+                format!("Download {}", url(&signature)),
+                format!("Download {} to {}", url(&asset_name()), binary),
+                format!(
+                    "Verify {} with {} and {}",
+                    asset_name(),
+                    CHECKSUM_FILE,
+                    signature
+                ),
+                format!("Move {} to {} with mode 0755", asset_name(), binary),
+                format!("Create {} with mode 0755", root.join(LAUNCHER).display()),
+                format!("Write the receipt {}", root.join(RECEIPT_FILE).display()),
+            ]
+        );
+
+        // planning must not touch the install root
+        assert!(!root.exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_from_bundle() {
+        let dir = env::temp_dir().join("limit-up-bundle-test");
+        fs::remove_dir_all(&dir).ok();
+        create_mirror(&dir.join("mirror"));
+
+        let mut config = InstallConfig {
+            install_root: dir.join("root").display().to_string(),
+            trusted_key: Some(TEST_PUBLIC_KEY.to_string()),
+            mirrors: vec![Mirror::parse(&format!("file://{}/mirror", dir.display()))],
+            extract: Some(false),
+            ..Default::default()
+        };
+        config.retry.attempts = 1;
+
+        let bundle = dir.join("bundle.tar.gz");
+        let source = bundle.display().to_string();
+        RT.block_on(super::bundle(
+            config.clone(),
+            Some(bundle.clone()),
+            Events::discard(),
+        ))
+        .unwrap();
+
+        // nothing should be downloaded while installing from the bundle
+        fs::remove_dir_all(dir.join("mirror")).unwrap();
+        config.bundle = Some(bundle);
+
+        RT.block_on(super::install(config, Events::discard()))
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("root").join(SERVER_BINARY)).unwrap(),
+            "limit"
+        );
+
+        let receipt = Receipt::load(dir.join("root")).unwrap().unwrap();
+        assert_eq!(receipt.version, "v0.1.0");
+        assert_eq!(receipt.source, source);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -3,8 +3,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use super::{
+    http::HttpConfig,
+    release::{Mirror, Version},
+    retry::RetryPolicy,
+};
+
 /// Return paths of the program
-#[allow(dead_code)]
 pub fn find_command(
     program: impl AsRef<Path>,
     other: impl IntoIterator<Item = impl Into<PathBuf>>, // extra path
@@ -27,11 +32,104 @@ pub fn find_command(
         .unwrap_or_default()
 }
 
-#[derive(Default)]
+/// How commands that need Root get the password of the user
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Elevation {
+    /// sudo, doas or pkexec prompt on the terminal themselves
+    #[default]
+    Terminal,
+    /// There is no terminal to prompt on, sudo reads the password given from stdin
+    /// and commands fail instead of prompting if none is given
+    Password(Option<Password>),
+}
+
+/// Password of the user, hidden from `Debug` so it never ends up in logs
+#[derive(Clone, PartialEq)]
+pub struct Password(String);
+
+impl Password {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Self(password)
+    }
+}
+
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("***")
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct InstallConfig {
     pub install_root: String,
+    /// Public key to verify release signatures with instead of the release key
+    pub trusted_key: Option<String>,
+    /// Which release to install
+    pub version: Version,
+    pub http: HttpConfig,
+    /// How network operations are retried
+    pub retry: RetryPolicy,
+    /// Where releases are downloaded from, in order of preference
+    pub mirrors: Vec<Mirror>,
+    /// Whether to extract the AppImage instead of running it with FUSE,
+    /// decided by whether FUSE is available if it is `None`
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub extract: Option<bool>,
+    /// Install missing dependencies with the package manager of the system
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub install_dependencies: bool,
+    /// How commands that need Root are elevated
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub elevation: Elevation,
+    /// Install from an offline bundle instead of downloading
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub bundle: Option<PathBuf>,
+    /// Where the source code of limit-server is cloned from, in order of preference
+    #[cfg_attr(not(target_os = "freebsd"), allow(dead_code))]
+    pub git_mirrors: Vec<String>,
+}
+
+impl Default for InstallConfig {
+    fn default() -> Self {
+        Self {
+            install_root: format!("{}/.limit-lab", env::var("HOME").unwrap_or_default()),
+            trusted_key: None,
+            version: Version::default(),
+            http: HttpConfig::default(),
+            retry: RetryPolicy::default(),
+            mirrors: vec![Mirror::GitHub],
+            extract: None,
+            install_dependencies: false,
+            elevation: Elevation::default(),
+            bundle: None,
+            git_mirrors: vec!["https://github.com/Limit-LAB/limit-server".to_string()],
+        }
+    }
 }
 
+/// Outcome of `update`
+#[derive(Debug)]
+pub enum UpdateResult {
+    /// The installed version is the latest one
+    UpToDate(String),
+    /// limit-server has been updated, `from` is `None` if the old version is unknown
+    Updated { from: Option<String>, to: String },
+}
+
+pub mod doctor;
+#[cfg(unix)]
+pub mod pkgmanager;
+#[cfg(unix)]
+pub mod privilege;
+
+mod_use::mod_use!(event, plan, receipt, status);
+
 #[cfg(target_os = "linux")]
 mod_use::mod_use!(linux_impl);
 
@@ -41,6 +139,9 @@ mod_use::mod_use!(freebsd_impl);
 #[cfg(target_os = "windows")]
 mod_use::mod_use!(windows_impl);
 
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "windows")))]
+mod_use::mod_use!(unsupported_impl);
+
 #[cfg(test)]
 mod tests {
     use std::env;
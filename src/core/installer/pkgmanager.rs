@@ -0,0 +1,405 @@
+use std::{fs, iter::empty, ops::Range};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    join,
+};
+
+use r18::tr;
+
+use super::{privilege::Privilege, Events, InstallEvent, Step, Stream};
+use crate::Result;
+
+// os-release files, the first one found is used
+const OS_RELEASE: &[&str] = &["/etc/os-release", "/usr/lib/os-release"];
+
+trait PkgManager {
+    fn install(&self, pkgs: &[String]) -> Vec<Step>;
+    fn uninstall(&self, pkgs: &[String]) -> Vec<Step>;
+    fn name(&self) -> &'static str;
+    /// IDs in os-release of distributions using this package manager
+    fn distros(&self) -> &'static [&'static str];
+    /// Packages providing a dependency, `None` if it is named the same
+    fn packages(&self, dependency: &str) -> Option<&'static [&'static str]>;
+}
+
+// build a step running the package manager with `args` followed by `pkgs`
+fn step(program: &'static str, args: &[&str], pkgs: &[String]) -> Step {
+    Step {
+        program,
+        args: args
+            .iter()
+            .map(|arg| arg.to_string())
+            .chain(pkgs.iter().cloned())
+            .collect(),
+    }
+}
+
+macro_rules! impl_pkg_manager {
+    (
+        $class:ident {
+            name: $name:expr,
+            install: [$($install:expr),+],
+            uninstall: [$($uninstall:expr),+],
+            update: [$($update:expr),*],
+            distros: [$($distro:expr),*],
+            packages: [$($dependency:expr => [$($package:expr),+]),*] $(,)?
+        }
+    ) => {
+        pub struct $class;
+
+        impl PkgManager for $class {
+            fn install(&self, pkgs: &[String]) -> Vec<Step> {
+                // {pkgmgr} {update} if it has one, then {pkgmgr} {install} <pkgs>
+                let update: &[&str] = &[$($update),*];
+
+                (!update.is_empty())
+                    .then(|| step($name, update, &[]))
+                    .into_iter()
+                    .chain([step($name, &[$($install),+], pkgs)])
+                    .collect()
+            }
+
+            fn uninstall(&self, pkgs: &[String]) -> Vec<Step> {
+                // {pkgmgr} {uninstall} <pkgs>
+                vec![step($name, &[$($uninstall),+], pkgs)]
+            }
+
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn distros(&self) -> &'static [&'static str] {
+                &[$($distro),*]
+            }
+
+            fn packages(&self, dependency: &str) -> Option<&'static [&'static str]> {
+                match dependency {
+                    $($dependency => Some(&[$($package),+]),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_pkg_manager!(Pkg {
+    name: "pkg",
+    install: ["install", "-y"],
+    uninstall: ["autoremove", "-y"],
+    update: ["update"],
+    distros: ["freebsd"],
+    packages: ["fuse" => ["fusefs-libs"]],
+});
+
+impl_pkg_manager!(Apt {
+    name: "apt-get",
+    install: ["install", "-y"],
+    uninstall: ["remove", "-y"],
+    update: ["update", "-y"],
+    distros: ["debian", "ubuntu"],
+    packages: ["fuse" => ["fuse3", "libfuse2"]],
+});
+
+impl_pkg_manager!(Dnf {
+    name: "dnf",
+    install: ["install", "-y"],
+    uninstall: ["remove", "-y"],
+    update: ["makecache", "-y"],
+    distros: ["fedora", "rhel", "centos"],
+    packages: ["fuse" => ["fuse", "fuse-libs"]],
+});
+
+impl_pkg_manager!(Yum {
+    name: "yum",
+    install: ["install", "-y"],
+    uninstall: ["remove", "-y"],
+    update: ["makecache", "-y"],
+    distros: ["fedora", "rhel", "centos"],
+    packages: ["fuse" => ["fuse", "fuse-libs"]],
+});
+
+// refreshing the databases without upgrading the system is a partial upgrade,
+// which Arch doesn't support, so packages are installed from the current databases
+impl_pkg_manager!(Pacman {
+    name: "pacman",
+    install: ["-S", "--needed", "--noconfirm"],
+    uninstall: ["-R", "--noconfirm"],
+    update: [],
+    distros: ["arch"],
+    packages: ["fuse" => ["fuse2"]],
+});
+
+impl_pkg_manager!(Zypper {
+    name: "zypper",
+    install: ["--non-interactive", "install"],
+    uninstall: ["--non-interactive", "remove"],
+    update: ["--non-interactive", "refresh"],
+    distros: ["opensuse", "suse", "sles"],
+    packages: ["fuse" => ["fuse", "libfuse2"]],
+});
+
+impl_pkg_manager!(Apk {
+    name: "apk",
+    install: ["add"],
+    uninstall: ["del"],
+    update: ["update"],
+    distros: ["alpine"],
+    packages: ["fuse" => ["fuse3", "fuse"]],
+});
+
+// package names are passed as arguments, so they can't be interpreted by a shell,
+// but one starting with `-` would still be taken as an option
+fn check_packages(pkgs: &[String]) -> Result<()> {
+    for pkg in pkgs {
+        let valid = pkg.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && pkg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+
+        if !valid {
+            Err(tr!("Invalid package name {}", pkg).to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+macro_rules! boxed_mgrs {
+    ($($mgr:ident),+) => {
+        vec![$(Box::new($mgr {})),+]
+    };
+}
+
+// return IDs of the running distribution and the ones it is like, the most specific first
+fn os_release_ids() -> Vec<String> {
+    OS_RELEASE
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|text| parse_os_release(&text))
+        .unwrap_or_default()
+}
+
+// parse ID and ID_LIKE of os-release
+fn parse_os_release(text: &str) -> Vec<String> {
+    let value = |key: &str| {
+        text.lines()
+            .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix('='))
+            .map(|value| {
+                value
+                    .trim()
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_string()
+            })
+            .unwrap_or_default()
+    };
+
+    let id = value("ID");
+    let like = value("ID_LIKE");
+
+    id.split_whitespace()
+        .chain(like.split_whitespace())
+        .map(|id| id.to_lowercase())
+        .collect()
+}
+
+pub struct PackageManager {
+    mgr: Box<dyn PkgManager + Send + Sync>,
+}
+
+impl PackageManager {
+    /// Find the package manager of the running system,
+    /// the one the distribution ships is preferred if there are several
+    pub fn new() -> Result<PackageManager> {
+        let mgrs: Vec<Box<dyn PkgManager + Send + Sync>> =
+            boxed_mgrs![Pkg, Apt, Dnf, Yum, Pacman, Zypper, Apk];
+
+        let mut mgrs: Vec<_> = mgrs
+            .into_iter()
+            .filter(|mgr| !super::find_command(mgr.name(), empty::<&str>()).is_empty())
+            .collect();
+
+        if mgrs.is_empty() {
+            Err(tr!("No supported package manager is found").to_string())?;
+        }
+
+        // derivatives list the distributions they are based on in ID_LIKE
+        let preferred = os_release_ids()
+            .iter()
+            .find_map(|id| {
+                mgrs.iter()
+                    .position(|mgr| mgr.distros().contains(&id.as_str()))
+            })
+            .unwrap_or(0);
+
+        Ok(PackageManager {
+            mgr: mgrs.swap_remove(preferred),
+        })
+    }
+
+    /// Return the packages providing a dependency, like `fuse`
+    pub fn packages(&self, dependency: &str) -> Vec<String> {
+        match self.mgr.packages(dependency) {
+            Some(packages) => packages.iter().map(|p| p.to_string()).collect(),
+            None => vec![dependency.to_string()],
+        }
+    }
+
+    /// Return the steps installing `pkgs`
+    pub fn install_steps(&self, pkgs: &[String]) -> Result<Vec<Step>> {
+        check_packages(pkgs)?;
+
+        Ok(self.mgr.install(pkgs))
+    }
+
+    /// Return the steps uninstalling `pkgs`
+    #[allow(dead_code)]
+    pub fn uninstall_steps(&self, pkgs: &[String]) -> Result<Vec<Step>> {
+        check_packages(pkgs)?;
+
+        Ok(self.mgr.uninstall(pkgs))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.mgr.name()
+    }
+}
+
+/// Run steps as Root with `privilege` in order until one of them fails,
+/// steps are reported as events with progress spread over `progress`
+pub async fn run(
+    privilege: &Privilege,
+    steps: &[Step],
+    progress: Range<usize>,
+    events: &Events,
+) -> Result<()> {
+    let total = steps.len();
+    privilege.authenticate().await?;
+
+    for (i, step) in steps.iter().enumerate() {
+        let progress = progress.start + progress.len() * i / total.max(1);
+        let (n, count, command) = ((i + 1).to_string(), total.to_string(), step.to_string());
+        let name = format!("[{}/{}] {}", n, count, command);
+
+        events.send(InstallEvent::StepStarted(name.clone()));
+        events.progress(progress);
+
+        let mut child = privilege
+            .spawn(&mut privilege.command(step.program, &step.args))
+            .await
+            .map_err(|e| tr!("Unable to run {}: {}", step.program, &e.to_string()).to_string())?;
+
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+
+        let (status, _, _) = join!(
+            child.wait(),
+            forward_lines(stdout, |line| events.log(Stream::Stdout, line)),
+            forward_lines(stderr, |line| events.log(Stream::Stderr, line)),
+        );
+        let status = status?;
+
+        if !status.success() {
+            Err(tr!(
+                "[{}/{}] {} failed ({})",
+                &n,
+                &count,
+                &command,
+                &status.to_string()
+            )
+            .to_string())?;
+        }
+
+        events.send(InstallEvent::StepFinished(name));
+    }
+
+    events.progress(progress.end);
+
+    Ok(())
+}
+
+/// Pass lines of an output stream of a process to `f` until it is closed
+pub(super) async fn forward_lines(stream: impl AsyncRead + Unpin, f: impl Fn(String)) {
+    let mut lines = BufReader::new(stream).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        f(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_packages, parse_os_release, Apk, Pacman, PkgManager, Zypper};
+
+    #[test]
+    fn test_parse_os_release() {
+        let ubuntu = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\nVERSION_ID=\"22.04\"\n";
+        assert_eq!(parse_os_release(ubuntu), ["ubuntu", "debian"]);
+
+        let rocky = "ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+        assert_eq!(
+            parse_os_release(rocky),
+            ["rocky", "rhel", "centos", "fedora"]
+        );
+
+        assert!(parse_os_release("NAME=Unknown\n").is_empty());
+    }
+
+    #[test]
+    fn test_install_steps() {
+        let steps = Zypper.install(&["fuse".to_string()]);
+
+        assert_eq!(
+            steps.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "zypper --non-interactive refresh",
+                "zypper --non-interactive install fuse"
+            ]
+        );
+
+        // pacman must not refresh without upgrading
+        assert_eq!(
+            Pacman
+                .install(&["fuse2".to_string()])
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["pacman -S --needed --noconfirm fuse2"]
+        );
+        assert_eq!(Apk.packages("fuse"), Some(&["fuse3", "fuse"][..]));
+
+        assert!(check_packages(&["fuse3".to_string(), "g++".to_string()]).is_ok());
+        for pkg in ["fuse; rm -rf /", "fuse libfuse2", "--from=evil", ""] {
+            assert!(check_packages(&[pkg.to_string()]).is_err());
+        }
+    }
+
+    // installs and removes a real package
+    #[cfg(target_os = "freebsd")]
+    #[test]
+    fn pkgmgr_test() {
+        use super::{run, Events, PackageManager};
+        use crate::core::{
+            installer::{privilege::Privilege, Elevation},
+            RT,
+        };
+
+        let (events, mut receiver) = Events::channel();
+        let pkgs = ["cowsay".to_string()];
+
+        RT.block_on(async {
+            let privilege = Privilege::detect(Elevation::Terminal).unwrap();
+            let mgr = PackageManager::new().unwrap();
+            println!("package manager: {}", mgr.name());
+
+            let steps = mgr.install_steps(&pkgs).unwrap();
+            run(&privilege, &steps, 0..100, &events).await.unwrap();
+            let steps = mgr.uninstall_steps(&pkgs).unwrap();
+            run(&privilege, &steps, 0..100, &events).await.unwrap();
+
+            drop(events);
+            while let Some(event) = receiver.recv().await {
+                println!("{:?}", event);
+            }
+        });
+    }
+}
@@ -0,0 +1,233 @@
+use std::{fmt, path::PathBuf};
+
+use r18::tr;
+
+use super::Receipt;
+
+/// A command run as argv, it never goes through a shell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub program: &'static str,
+    pub args: Vec<String>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        self.args.iter().try_for_each(|arg| write!(f, " {}", arg))
+    }
+}
+
+/// A discrete step of an install, update or uninstall.
+///
+/// Installers plan their actions before executing them,
+/// so that a dry run can print the plan instead.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Install `packages` by running `steps` of `manager` as Root
+    Packages {
+        manager: &'static str,
+        packages: Vec<String>,
+        steps: Vec<Step>,
+    },
+    /// Download `name` from the first of `urls` that works,
+    /// into `target` or into memory if it is `None`
+    Download {
+        name: String,
+        urls: Vec<String>,
+        target: Option<PathBuf>,
+    },
+    /// Verify a download with the checksum file and the signature downloaded before it
+    Verify {
+        name: String,
+        checksums: String,
+        signature: String,
+    },
+    /// Move a verified download to `path` and set its mode
+    Install {
+        name: String,
+        path: PathBuf,
+        mode: u32,
+    },
+    /// Extract a verified AppImage into `target`
+    Extract { name: String, target: PathBuf },
+    /// Unpack an offline bundle into `target`
+    Unpack { bundle: PathBuf, target: PathBuf },
+    /// Create a directory and its parents
+    CreateDir(PathBuf),
+    /// Write `contents` into `path` and set its mode
+    WriteFile {
+        path: PathBuf,
+        mode: u32,
+        contents: String,
+    },
+    /// Write the receipt into `root`, what is only known once installed is filled in then
+    WriteReceipt {
+        root: PathBuf,
+        receipt: Box<Receipt>,
+    },
+    /// Clone the git repository at the first of `urls` that works into `path`
+    #[cfg_attr(not(target_os = "freebsd"), allow(dead_code))]
+    GitClone { urls: Vec<String>, path: PathBuf },
+    /// Pull the git repository at `path` from the first of `urls` that works
+    #[cfg_attr(not(target_os = "freebsd"), allow(dead_code))]
+    GitPull { urls: Vec<String>, path: PathBuf },
+    /// Stop limit-server if the supervisor of limit-up in `root` is running it
+    StopServer(PathBuf),
+    /// Stop and remove a service definition
+    RemoveService(PathBuf),
+    /// Remove a file or a directory
+    Remove(PathBuf),
+}
+
+// list the mirrors after the first one on their own lines
+fn fallbacks(f: &mut fmt::Formatter<'_>, urls: &[String]) -> fmt::Result {
+    urls.iter()
+        .skip(1)
+        .try_for_each(|url| write!(f, "\n    {}", tr!("or {}", url)))
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = |mode: &u32| format!("{:04o}", mode);
+        let first = |urls: &[String]| urls.first().cloned().unwrap_or_default();
+
+        match self {
+            Action::Packages {
+                manager,
+                packages,
+                steps,
+            } => {
+                write!(
+                    f,
+                    "{}",
+                    tr!(
+                        "Install {} with {} as Root:",
+                        packages.join(", "),
+                        manager.to_string()
+                    )
+                )?;
+                steps
+                    .iter()
+                    .try_for_each(|step| write!(f, "\n    {}", step))
+            }
+            Action::Download {
+                urls,
+                target: Some(target),
+                ..
+            } => {
+                write!(
+                    f,
+                    "{}",
+                    tr!(
+                        "Download {} to {}",
+                        first(urls),
+                        target.display().to_string()
+                    )
+                )?;
+                fallbacks(f, urls)
+            }
+            Action::Download { urls, .. } => {
+                write!(f, "{}", tr!("Download {}", first(urls)))?;
+                fallbacks(f, urls)
+            }
+            Action::Verify {
+                name,
+                checksums,
+                signature,
+            } => write!(
+                f,
+                "{}",
+                tr!("Verify {} with {} and {}", name, checksums, signature)
+            ),
+            Action::Install {
+                name,
+                path,
+                mode: m,
+            } => write!(
+                f,
+                "{}",
+                tr!(
+                    "Move {} to {} with mode {}",
+                    name.to_string(),
+                    path.display().to_string(),
+                    mode(m)
+                )
+            ),
+            Action::Extract { name, target } => write!(
+                f,
+                "{}",
+                tr!(
+                    "Extract {} into {}",
+                    name.to_string(),
+                    target.display().to_string()
+                )
+            ),
+            Action::Unpack { bundle, target } => write!(
+                f,
+                "{}",
+                tr!(
+                    "Unpack {} into {}",
+                    bundle.display().to_string(),
+                    target.display().to_string()
+                )
+            ),
+            Action::CreateDir(path) => write!(
+                f,
+                "{}",
+                tr!("Create directory {}", path.display().to_string())
+            ),
+            Action::WriteFile { path, mode: m, .. } => write!(
+                f,
+                "{}",
+                tr!(
+                    "Create {} with mode {}",
+                    path.display().to_string(),
+                    mode(m)
+                )
+            ),
+            Action::WriteReceipt { root, .. } => write!(
+                f,
+                "{}",
+                tr!(
+                    "Write the receipt {}",
+                    Receipt::path(root).display().to_string()
+                )
+            ),
+            Action::GitClone { urls, path } => {
+                write!(
+                    f,
+                    "git clone --recursive {} {}",
+                    first(urls),
+                    path.display()
+                )?;
+                fallbacks(f, urls)
+            }
+            Action::GitPull { urls, path } => {
+                write!(
+                    f,
+                    "git -C {} pull --recurse-submodules {}",
+                    path.display(),
+                    first(urls)
+                )?;
+                fallbacks(f, urls)
+            }
+            Action::StopServer(root) => write!(
+                f,
+                "{}",
+                tr!(
+                    "Stop limit-server supervised in {}",
+                    root.display().to_string()
+                )
+            ),
+            Action::RemoveService(path) => write!(
+                f,
+                "{}",
+                tr!("Stop and remove service {}", path.display().to_string())
+            ),
+            Action::Remove(path) => {
+                write!(f, "{}", tr!("Remove {}", path.display().to_string()))
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::{iter::empty, process::Stdio};
+
+use r18::tr;
+use tokio::{
+    io::AsyncWriteExt,
+    process::{Child, Command},
+};
+
+use super::{find_command, Elevation};
+use crate::core::helper::Help;
+
+// tools running commands as Root, in order of preference
+const TOOLS: &[Tool] = &[Tool::Sudo, Tool::Doas, Tool::Pkexec];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tool {
+    Sudo,
+    Doas,
+    Pkexec,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Sudo => "sudo",
+            Tool::Doas => "doas",
+            Tool::Pkexec => "pkexec",
+        }
+    }
+}
+
+/// Runs commands that need Root, elevated with sudo, doas or pkexec
+/// unless limit-up is running as Root already
+#[derive(Debug, Clone)]
+pub struct Privilege {
+    // `None` if running as Root
+    tool: Option<Tool>,
+    elevation: Elevation,
+}
+
+impl Privilege {
+    /// Find how to run commands as Root
+    pub fn detect(elevation: Elevation) -> crate::Result<Self> {
+        if nix::unistd::Uid::effective().is_root() {
+            return Ok(Self {
+                tool: None,
+                elevation,
+            });
+        }
+
+        let tool = TOOLS
+            .iter()
+            .find(|tool| !find_command(tool.name(), empty::<&str>()).is_empty())
+            .ok_or_else(|| {
+                tr!(
+                    "Root is required, but none of sudo, doas and pkexec is found\n\n{}",
+                    Help::Root.to_string()
+                )
+                .to_string()
+            })?;
+
+        Ok(Self {
+            tool: Some(*tool),
+            elevation,
+        })
+    }
+
+    /// Return the tool commands are elevated with, `None` if running as Root
+    pub fn tool(&self) -> Option<&'static str> {
+        self.tool.map(Tool::name)
+    }
+
+    /// Check that commands can be elevated, the password given is verified if there is one
+    pub async fn authenticate(&self) -> crate::Result<()> {
+        // pkexec asks through a polkit agent and can't be checked beforehand
+        let tool = match self.tool {
+            None | Some(Tool::Pkexec) => return Ok(()),
+            Some(tool) => tool,
+        };
+
+        let mut command = self.command("true", &[]);
+        let output = self.spawn(&mut command).await?.wait_with_output().await?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        Err(tr!(
+            "Unable to run commands as Root with {}: {}\n\n{}",
+            tool.name().to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            Help::Root.to_string()
+        )
+        .into())
+    }
+
+    /// Build a command running `program` as Root
+    pub fn command(&self, program: &str, args: &[String]) -> Command {
+        let Some(tool) = self.tool else {
+            let mut command = Command::new(program);
+            command.args(args);
+            return command;
+        };
+
+        let mut command = Command::new(tool.name());
+        match (tool, &self.elevation) {
+            // read the password from stdin without a prompt, cached credentials are ignored
+            // so that the password is always read by sudo instead of the program
+            (Tool::Sudo, Elevation::Password(Some(_))) => command.args(["-k", "-S", "-p", ""]),
+            // never prompt, doas reads passwords from the terminal only
+            (Tool::Sudo, Elevation::Password(None)) | (Tool::Doas, Elevation::Password(_)) => {
+                command.arg("-n")
+            }
+            _ => &mut command,
+        };
+
+        if tool != Tool::Pkexec {
+            command.arg("--");
+        }
+
+        command.arg(program).args(args);
+        command
+    }
+
+    /// Spawn a command built by `command` with its output piped,
+    /// the password is written to its stdin if sudo reads it from there
+    pub async fn spawn(&self, command: &mut Command) -> crate::Result<Child> {
+        let password = match (self.tool, &self.elevation) {
+            (Some(Tool::Sudo), Elevation::Password(Some(password))) => Some(password),
+            _ => None,
+        };
+
+        let mut child = command
+            .stdin(match password {
+                Some(_) => Stdio::piped(),
+                None => Stdio::null(),
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(password) = password {
+            let mut stdin = child.stdin.take().unwrap();
+            stdin
+                .write_all(format!("{}\n", password.as_str()).as_bytes())
+                .await?;
+        }
+
+        Ok(child)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Privilege, Tool};
+    use crate::core::installer::Elevation;
+
+    #[test]
+    fn test_command() {
+        let args = ["install".to_string(), "fuse".to_string()];
+        let command = |tool, elevation| {
+            let privilege = Privilege { tool, elevation };
+            let command = privilege.command("apt-get", &args);
+            let command = command.as_std();
+
+            std::iter::once(command.get_program())
+                .chain(command.get_args())
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        assert_eq!(command(None, Elevation::Terminal), "apt-get install fuse");
+        assert_eq!(
+            command(Some(Tool::Sudo), Elevation::Terminal),
+            "sudo -- apt-get install fuse"
+        );
+        assert_eq!(
+            command(
+                Some(Tool::Sudo),
+                Elevation::Password(Some("secret".to_string().into()))
+            ),
+            "sudo -k -S -p  -- apt-get install fuse"
+        );
+        assert_eq!(
+            command(Some(Tool::Doas), Elevation::Password(None)),
+            "doas -n -- apt-get install fuse"
+        );
+        assert_eq!(
+            command(Some(Tool::Pkexec), Elevation::Terminal),
+            "pkexec apt-get install fuse"
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_password() {
+        let privilege = Privilege {
+            tool: Some(Tool::Sudo),
+            elevation: Elevation::Password(Some("secret".to_string().into())),
+        };
+
+        let debug = format!("{:?}", privilege);
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains("***"));
+    }
+}
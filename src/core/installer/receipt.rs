@@ -0,0 +1,115 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the receipt in the install root
+pub const RECEIPT_FILE: &str = "limit-up.receipt.json";
+
+/// What `install` did, written into the install root after a successful install
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Receipt {
+    /// Installed version, a release tag or a git revision
+    pub version: String,
+    /// When the installed release was published, an RFC 3339 timestamp
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// Where limit-server was installed from
+    pub source: String,
+    /// SHA-256 checksum of the installed binary, or of the directory it is extracted
+    /// into, which is the first of `files`
+    pub sha256: Option<String>,
+    /// Unix timestamp of the install
+    pub installed_at: u64,
+    /// Files and directories created by the install, relative to the install root
+    pub files: Vec<PathBuf>,
+    /// Packages installed through the package manager
+    pub dependencies: Vec<String>,
+    /// Command running limit-server, relative to the install root
+    #[serde(default)]
+    pub command: Option<PathBuf>,
+    /// Service definitions installed for limit-server
+    #[serde(default)]
+    pub services: Vec<PathBuf>,
+}
+
+impl Receipt {
+    /// Create a receipt of an install that happened just now
+    pub fn new(version: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            published_at: None,
+            source: source.into(),
+            sha256: None,
+            installed_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            files: Vec::new(),
+            dependencies: Vec::new(),
+            command: None,
+            services: Vec::new(),
+        }
+    }
+
+    /// Return the path of the receipt in `root`
+    pub fn path(root: impl AsRef<Path>) -> PathBuf {
+        root.as_ref().join(RECEIPT_FILE)
+    }
+
+    /// Read the receipt in `root`, `None` if limit-server is not installed there
+    pub fn load(root: impl AsRef<Path>) -> crate::Result<Option<Self>> {
+        match fs::read_to_string(Self::path(root)) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the receipt into `root`, replacing the old one
+    pub fn save(&self, root: impl AsRef<Path>) -> crate::Result<()> {
+        let path = Self::path(root);
+
+        // a half written receipt is worse than an outdated one
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+
+        fs::write(&temp, serde_json::to_string_pretty(self)? + "\n")?;
+        fs::rename(&temp, &path)?;
+
+        Ok(())
+    }
+
+    /// Return when limit-server was installed
+    pub fn installed_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.installed_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use super::Receipt;
+
+    #[test]
+    fn test_receipt() {
+        let dir = env::temp_dir().join("limit-up-receipt-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        assert!(Receipt::load(&dir).unwrap().is_none());
+
+        let mut receipt = Receipt::new("v0.1.0", "https://example.com/limit");
+        receipt.sha256 = Some("00".repeat(32));
+        receipt.files.push(PathBuf::from("limit-server.Appimage"));
+        receipt.save(&dir).unwrap();
+
+        assert_eq!(Receipt::load(&dir).unwrap(), Some(receipt));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
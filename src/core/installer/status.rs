@@ -0,0 +1,124 @@
+use std::{
+    fs,
+    iter::empty,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::{find_command, required_commands, InstallConfig, Receipt};
+use crate::core::checksum;
+
+/// Health of an installation
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub install_root: String,
+    pub installed: bool,
+    pub version: Option<String>,
+    /// Commands limit-server needs to run
+    pub commands: Vec<Command>,
+    /// `None` if processes can't be inspected on this platform
+    pub running: Option<bool>,
+    /// `None` if no checksum was recorded
+    pub checksum_ok: Option<bool>,
+    pub receipt: Option<Receipt>,
+}
+
+/// A command limit-server needs, `path` is `None` if it can't be found
+#[derive(Debug, Serialize)]
+pub struct Command {
+    pub name: String,
+    pub path: Option<PathBuf>,
+}
+
+impl Command {
+    /// Look a command up in PATH
+    pub fn find(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            path: find_command(name, empty::<&str>()).into_iter().next(),
+        }
+    }
+}
+
+impl Status {
+    /// Whether limit-server is installed and nothing it needs is missing or broken
+    pub fn is_healthy(&self) -> bool {
+        self.installed
+            && self.commands.iter().all(|command| command.path.is_some())
+            && self.checksum_ok != Some(false)
+    }
+}
+
+/// Inspect the installation in the install root of `config`
+pub fn status(config: &InstallConfig) -> crate::Result<Status> {
+    let root = Path::new(&config.install_root);
+    let receipt = Receipt::load(root)?;
+
+    let commands = required_commands(receipt.as_ref());
+
+    // the binary, or the directory it is extracted into, is the first file of the receipt
+    let binary = receipt
+        .as_ref()
+        .and_then(|receipt| receipt.files.first())
+        .map(|file| root.join(file));
+
+    let checksum_ok = match (&receipt, &binary) {
+        (
+            Some(Receipt {
+                sha256: Some(expected),
+                ..
+            }),
+            Some(binary),
+        ) => Some(checksum::sha256_path(binary).is_ok_and(|actual| &actual == expected)),
+        _ => None,
+    };
+
+    Ok(Status {
+        install_root: config.install_root.clone(),
+        installed: receipt.is_some(),
+        version: receipt.as_ref().map(|receipt| receipt.version.clone()),
+        commands,
+        running: binary.and_then(|binary| is_running(&binary)),
+        checksum_ok,
+        receipt,
+    })
+}
+
+// whether a process is running `binary` or anything inside it if it is a directory,
+// `None` if there is no procfs to tell
+fn is_running(binary: &Path) -> Option<bool> {
+    if !Path::new("/proc/self/exe").exists() {
+        return None;
+    }
+
+    let binary = fs::canonicalize(binary).unwrap_or_else(|_| binary.to_path_buf());
+
+    Some(
+        fs::read_dir("/proc")
+            .ok()?
+            .filter_map(|entry| fs::read_link(entry.ok()?.path().join("exe")).ok())
+            .any(|exe| {
+                // the binary may have been replaced by an update while running
+                let exe = exe.to_string_lossy();
+                Path::new(exe.trim_end_matches(" (deleted)")).starts_with(&binary)
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::is_running;
+
+    #[test]
+    fn test_is_running() {
+        let this = env::current_exe().unwrap();
+
+        if let Some(running) = is_running(&this) {
+            assert!(running);
+            assert_eq!(is_running(&this.with_extension("missing")), Some(false));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use r18::tr;
+
+use super::{doctor::Check, Action, Command, Events, InstallConfig, Receipt, UpdateResult};
+
+/// Commands limit-server installed as `receipt` says needs to run
+pub fn required_commands(_receipt: Option<&Receipt>) -> Vec<Command> {
+    Vec::new()
+}
+
+pub async fn doctor(_config: &InstallConfig) -> crate::Result<Vec<Check>> {
+    Err(tr!("Unsupported platform").into())
+}
+
+pub async fn install(_config: InstallConfig, events: Events) -> crate::Result<()> {
+    events.finish(Err(tr!("Unsupported platform").into()))
+}
+
+pub async fn install_plan(_config: &InstallConfig) -> crate::Result<Vec<Action>> {
+    Err(tr!("Unsupported platform").into())
+}
+
+pub async fn update(_config: InstallConfig, events: Events) -> crate::Result<UpdateResult> {
+    events.finish(Err(tr!("Unsupported platform").into()))
+}
+
+pub async fn update_plan(_config: &InstallConfig) -> crate::Result<(UpdateResult, Vec<Action>)> {
+    Err(tr!("Unsupported platform").into())
+}
+
+pub fn uninstall_plan(_config: &InstallConfig, _purge: bool) -> crate::Result<Vec<Action>> {
+    Err(tr!("Unsupported platform").into())
+}
+
+pub async fn uninstall(
+    _config: InstallConfig,
+    _plan: Vec<Action>,
+    events: Events,
+) -> crate::Result<()> {
+    events.finish(Err(tr!("Unsupported platform").into()))
+}
+
+pub async fn bundle(
+    _config: InstallConfig,
+    _output: Option<PathBuf>,
+    events: Events,
+) -> crate::Result<PathBuf> {
+    events.finish(Err(tr!("Unsupported platform").into()))
+}
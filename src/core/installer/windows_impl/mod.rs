@@ -1,10 +1,52 @@
-pub async fn install(
-    config: super::InstallConfig,
-    callback: impl Fn(usize) + Send + 'static,
-) -> crate::Result<()> {
+/// Commands limit-server installed as `receipt` says needs to run
+pub fn required_commands(_receipt: Option<&super::Receipt>) -> Vec<super::Command> {
+    Vec::new()
+}
+
+pub async fn doctor(_config: &super::InstallConfig) -> crate::Result<Vec<super::doctor::Check>> {
+    Err("Unsupported platform".into())
+}
+
+pub async fn install(_config: super::InstallConfig, events: super::Events) -> crate::Result<()> {
+    events.finish(Err("Unsupported platform".into()))
+}
+
+pub async fn install_plan(_config: &super::InstallConfig) -> crate::Result<Vec<super::Action>> {
     Err("Unsupported platform".into())
 }
 
-pub async fn update() {}
+pub async fn update(
+    _config: super::InstallConfig,
+    events: super::Events,
+) -> crate::Result<super::UpdateResult> {
+    events.finish(Err("Unsupported platform".into()))
+}
 
-pub async fn uninstall() {}
+pub async fn update_plan(
+    _config: &super::InstallConfig,
+) -> crate::Result<(super::UpdateResult, Vec<super::Action>)> {
+    Err("Unsupported platform".into())
+}
+
+pub fn uninstall_plan(
+    _config: &super::InstallConfig,
+    _purge: bool,
+) -> crate::Result<Vec<super::Action>> {
+    Err("Unsupported platform".into())
+}
+
+pub async fn uninstall(
+    _config: super::InstallConfig,
+    _plan: Vec<super::Action>,
+    events: super::Events,
+) -> crate::Result<()> {
+    events.finish(Err("Unsupported platform".into()))
+}
+
+pub async fn bundle(
+    _config: super::InstallConfig,
+    _output: Option<std::path::PathBuf>,
+    events: super::Events,
+) -> crate::Result<std::path::PathBuf> {
+    events.finish(Err("Unsupported platform".into()))
+}
@@ -1,9 +1,6 @@
 use once_cell::sync::Lazy;
-use reqwest::Client;
 use tokio::runtime::{Builder, Runtime};
 
-static HTTP_CLIENT: Lazy<Client> = Lazy::new(Client::new);
-
 // global tokio runtime
 pub static RT: Lazy<Runtime> = Lazy::new(|| {
     Builder::new_multi_thread()
@@ -13,5 +10,15 @@ pub static RT: Lazy<Runtime> = Lazy::new(|| {
         .expect("Failed to create multi-thread runtime")
 });
 
+pub mod checksum;
+pub mod config;
+pub mod download;
 pub mod helper;
+pub mod http;
 pub mod installer;
+pub mod release;
+pub mod retry;
+#[cfg(unix)]
+pub mod service;
+pub mod signature;
+pub mod tls;
@@ -0,0 +1,323 @@
+use std::{fmt, str::FromStr};
+
+use r18::tr;
+use serde::{Deserialize, Serialize};
+
+use reqwest::StatusCode;
+
+use super::{download::fetch_text, http};
+
+static RELEASES_API: &str = "https://api.github.com/repos/Limit-LAB/limit-server/releases";
+static GITHUB_RELEASES: &str = "https://github.com/Limit-LAB/limit-server/releases";
+
+/// Index file of a mirror, listing releases from the newest to the oldest
+pub const MIRROR_INDEX: &str = "releases.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    /// When the release was published, an RFC 3339 timestamp
+    #[serde(default)]
+    pub published_at: Option<String>,
+}
+
+impl Release {
+    /// Return whether this is a nightly build
+    pub fn is_nightly(&self) -> bool {
+        self.tag_name.starts_with("nightly")
+    }
+
+    /// Return whether this release belongs to the channel
+    pub fn in_channel(&self, channel: Channel) -> bool {
+        !self.draft
+            && match channel {
+                Channel::Stable => !self.prerelease && !self.is_nightly(),
+                Channel::Prerelease => !self.is_nightly(),
+                Channel::Nightly => self.is_nightly(),
+            }
+    }
+}
+
+/// Release channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Channel {
+    #[default]
+    Stable,
+    /// Stable releases and release candidates
+    Prerelease,
+    Nightly,
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stable" => Ok(Channel::Stable),
+            "prerelease" => Ok(Channel::Prerelease),
+            "nightly" => Ok(Channel::Nightly),
+            _ => Err(tr!(
+                "Unknown channel {}, expected stable, prerelease or nightly",
+                s
+            )
+            .to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Channel::Stable => write!(f, "stable"),
+            Channel::Prerelease => write!(f, "prerelease"),
+            Channel::Nightly => write!(f, "nightly"),
+        }
+    }
+}
+
+/// Which release of limit-server to install
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Version {
+    /// The latest release of a channel
+    Latest(Channel),
+    /// A specific release
+    Tag(String),
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::Latest(Channel::Stable)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Version::Latest(channel) => write!(f, "{}", tr!("latest {}", channel.to_string())),
+            Version::Tag(tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+/// Where releases are downloaded from
+///
+/// Except GitHub, a mirror is an HTTP(S) or `file://` directory laid out as
+///
+/// ```text
+/// releases.json          # [{ "tag_name": "v0.1.0", "prerelease": false, "published_at": "2023-10-01T00:00:00Z" }, ...],
+///                        # the newest first
+/// <tag>/<asset>          # release assets, checksums and signatures
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mirror {
+    GitHub,
+    Url(String),
+}
+
+impl Mirror {
+    /// Parse a mirror given by the user, `github` stands for the official releases
+    pub fn parse(mirror: &str) -> Self {
+        match mirror.trim_end_matches('/') {
+            "github" | "" => Mirror::GitHub,
+            url if url == GITHUB_RELEASES => Mirror::GitHub,
+            url => Mirror::Url(url.to_string()),
+        }
+    }
+
+    /// Return the URL of a file attached to a release
+    pub fn asset_url(&self, release: &Release, name: &str) -> String {
+        match self {
+            Mirror::GitHub => format!("{}/download/{}/{}", GITHUB_RELEASES, release.tag_name, name),
+            Mirror::Url(base) => format!("{}/{}/{}", base, release.tag_name, name),
+        }
+    }
+
+    /// List releases of limit-server, from the newest to the oldest
+    pub async fn releases(&self) -> crate::Result<Vec<Release>> {
+        match self {
+            Mirror::GitHub => {
+                let resp = http::read(
+                    http::client()
+                        .get(format!("{}?per_page=100", RELEASES_API))
+                        .send(),
+                )
+                .await?
+                .error_for_status()?;
+
+                http::read(resp.json()).await
+            }
+            Mirror::Url(base) => {
+                let index = fetch_text(&format!("{}/{}", base, MIRROR_INDEX)).await?;
+
+                Ok(serde_json::from_str(&index)?)
+            }
+        }
+    }
+
+    /// Find the release matching `version`
+    pub async fn resolve(&self, version: &Version) -> crate::Result<Release> {
+        let release = match (self, version) {
+            // only the newest releases are listed, look older ones up by their tags
+            (Mirror::GitHub, Version::Tag(tag)) => github_release(tag).await?,
+            (_, Version::Latest(channel)) => self
+                .releases()
+                .await?
+                .into_iter()
+                .find(|r| r.in_channel(*channel)),
+            (_, Version::Tag(tag)) => self
+                .releases()
+                .await?
+                .into_iter()
+                .find(|r| same_tag(&r.tag_name, tag)),
+        };
+
+        release.ok_or_else(|| {
+            tr!(
+                "No release matching {} found in {}",
+                version.to_string(),
+                self.to_string()
+            )
+            .into()
+        })
+    }
+}
+
+impl fmt::Display for Mirror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mirror::GitHub => write!(f, "{}", GITHUB_RELEASES),
+            Mirror::Url(base) => write!(f, "{}", base),
+        }
+    }
+}
+
+// fetch the GitHub release tagged `tag`, with or without the leading `v`
+async fn github_release(tag: &str) -> crate::Result<Option<Release>> {
+    let tags = match tag.strip_prefix('v') {
+        Some(bare) => [tag.to_string(), bare.to_string()],
+        None => [tag.to_string(), format!("v{}", tag)],
+    };
+
+    for tag in tags {
+        let resp = http::read(
+            http::client()
+                .get(format!("{}/tags/{}", RELEASES_API, tag))
+                .send(),
+        )
+        .await?;
+
+        if resp.status() == StatusCode::NOT_FOUND {
+            continue;
+        }
+
+        return http::read(resp.error_for_status()?.json()).await.map(Some);
+    }
+
+    Ok(None)
+}
+
+// return whether two tags name the same release, `v0.1.0` and `0.1.0` do
+fn same_tag(a: &str, b: &str) -> bool {
+    a == b || a.trim_start_matches('v') == b.trim_start_matches('v')
+}
+
+/// Return whether `latest` is newer than the release tagged `installed`, published at
+/// `installed_published_at`. Tags that are not semantic versions are ordered by
+/// when they were published, and any other release is newer if that is unknown
+pub fn is_newer(latest: &Release, installed: &str, installed_published_at: Option<&str>) -> bool {
+    let parse = |tag: &str| semver::Version::parse(tag.trim_start_matches('v'));
+    if let (Ok(latest), Ok(installed)) = (parse(&latest.tag_name), parse(installed)) {
+        return latest > installed;
+    }
+
+    let published = |at: &str| humantime::parse_rfc3339_weak(at).ok();
+    match (
+        latest.published_at.as_deref().and_then(published),
+        installed_published_at.and_then(published),
+    ) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => !same_tag(&latest.tag_name, installed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_newer, Channel, Mirror, Release};
+
+    #[test]
+    fn test_is_newer() {
+        let release = |tag: &str, published_at: Option<&str>| Release {
+            tag_name: tag.to_string(),
+            prerelease: false,
+            draft: false,
+            published_at: published_at.map(str::to_string),
+        };
+
+        assert!(is_newer(&release("v0.2.0", None), "v0.1.9", None));
+        assert!(is_newer(&release("0.10.0", None), "v0.9.0", None));
+        assert!(!is_newer(&release("v0.1.0", None), "v0.1.0", None));
+        assert!(!is_newer(&release("v0.1.0", None), "v0.2.0-rc.1", None));
+
+        // nightly tags are ordered by when they were published
+        let nightly = release("nightly-b", Some("2023-10-02T00:00:00Z"));
+        assert!(is_newer(
+            &nightly,
+            "nightly-a",
+            Some("2023-10-01T00:00:00Z")
+        ));
+        assert!(!is_newer(
+            &nightly,
+            "nightly-c",
+            Some("2023-10-03T00:00:00Z")
+        ));
+        assert!(!is_newer(
+            &nightly,
+            "nightly-b",
+            Some("2023-10-02T00:00:00Z")
+        ));
+
+        // with no timestamp to compare, any other tag is taken as newer
+        assert!(is_newer(&nightly, "nightly-a", None));
+        assert!(!is_newer(&release("nightly", None), "nightly", None));
+    }
+
+    #[test]
+    fn test_channel() {
+        let release = |tag: &str, prerelease| Release {
+            tag_name: tag.to_string(),
+            prerelease,
+            draft: false,
+            published_at: None,
+        };
+
+        assert!(release("v0.1.0", false).in_channel(Channel::Stable));
+        assert!(release("v0.1.0", false).in_channel(Channel::Prerelease));
+        assert!(!release("v0.2.0-rc.1", true).in_channel(Channel::Stable));
+        assert!(release("v0.2.0-rc.1", true).in_channel(Channel::Prerelease));
+        assert!(!release("nightly-20231001", true).in_channel(Channel::Prerelease));
+        assert!(release("nightly-20231001", true).in_channel(Channel::Nightly));
+    }
+
+    #[test]
+    fn test_mirror_asset_url() {
+        let release = Release {
+            tag_name: "v0.1.0".to_string(),
+            prerelease: false,
+            draft: false,
+            published_at: None,
+        };
+
+        assert_eq!(
+            Mirror::parse("github").asset_url(&release, "SHA256SUMS"),
+            "https://github.com/Limit-LAB/limit-server/releases/download/v0.1.0/SHA256SUMS"
+        );
+        assert_eq!(
+            Mirror::parse("file:///srv/limit/").asset_url(&release, "SHA256SUMS"),
+            "file:///srv/limit/v0.1.0/SHA256SUMS"
+        );
+    }
+}
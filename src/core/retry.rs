@@ -0,0 +1,122 @@
+use std::{future::Future, io, time::Duration};
+
+use rand::Rng;
+
+/// How network operations are retried on transient errors
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub attempts: u32,
+    /// Delay before the first retry, it doubles on every retry
+    pub backoff: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_backoff: Duration,
+    /// Randomize delays so that clients don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Return the delay before the given attempt (starting from 2)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(2)))
+            .min(self.max_backoff);
+
+        match self.jitter {
+            // "equal jitter", wait at least half of the delay
+            true => delay / 2 + delay.mul_f64(rand::thread_rng().gen_range(0.0..0.5)),
+            false => delay,
+        }
+    }
+
+    /// Run `f` until it succeeds, fails with a permanent error or runs out of attempts,
+    /// `on_retry` is called with the failed attempt, total attempts and the error before
+    /// waiting for the next attempt
+    pub async fn run<T, Fut>(
+        &self,
+        mut f: impl FnMut() -> Fut,
+        on_retry: impl Fn(u32, u32, &crate::Error),
+    ) -> crate::Result<T>
+    where
+        Fut: Future<Output = crate::Result<T>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match f().await {
+                Err(e) if attempt < self.attempts && is_transient(&e) => {
+                    on_retry(attempt, self.attempts, &e);
+
+                    attempt += 1;
+                    tokio::time::sleep(self.delay(attempt)).await;
+                }
+                res => break res,
+            }
+        }
+    }
+}
+
+/// Return whether an error is worth retrying
+pub fn is_transient(e: &crate::Error) -> bool {
+    if let Some(e) = e.downcast_ref::<io::Error>() {
+        return e.kind() == io::ErrorKind::TimedOut;
+    }
+
+    e.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| match e.status() {
+            Some(status) => status.is_server_error() || status.as_u16() == 429,
+            None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use super::RetryPolicy;
+    use crate::core::RT;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+
+        assert_eq!(policy.delay(2), Duration::from_secs(1));
+        assert_eq!(policy.delay(4), Duration::from_secs(4));
+        assert_eq!(policy.delay(10), Duration::from_secs(30));
+
+        let policy = RetryPolicy::default();
+        let delay = policy.delay(3);
+        assert!(delay >= Duration::from_secs(1) && delay < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_permanent_error() {
+        let calls = Cell::new(0);
+
+        let res: crate::Result<()> = RT.block_on(RetryPolicy::default().run(
+            || {
+                calls.set(calls.get() + 1);
+                async { Err("permanent".into()) }
+            },
+            |_, _, _| unreachable!(),
+        ));
+
+        assert!(res.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+}
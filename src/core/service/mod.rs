@@ -0,0 +1,399 @@
+pub mod openrc;
+pub mod supervisor;
+pub mod systemd;
+
+use std::{
+    env,
+    fs::{self, Permissions},
+    os::unix::prelude::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use nix::unistd::{Group, Uid, User};
+use r18::tr;
+use tokio::process::Command;
+
+use super::{
+    config::write_private,
+    installer::{privilege::Privilege, InstallConfig, Receipt},
+};
+
+/// Where a service is installed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scope {
+    #[default]
+    System,
+    /// Services of the current user, which don't require Root
+    User,
+}
+
+/// How limit-server runs as a service
+#[derive(Debug, Clone, Default)]
+pub struct ServiceConfig {
+    pub scope: Scope,
+    /// Working directory of limit-server, the install root if it is `None`
+    pub working_directory: Option<PathBuf>,
+    pub environment: Vec<(String, String)>,
+    /// User a system service runs limit-server as, the user installing it if it is `None`
+    pub user: Option<String>,
+}
+
+/// Init systems limit-server can run under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitSystem {
+    Systemd(Scope),
+    OpenRc,
+    /// The supervisor built into limit-up, for systems without an init system
+    Supervisor,
+}
+
+// return the install root and the receipt of limit-server installed there
+fn installed(config: &InstallConfig) -> crate::Result<(PathBuf, Receipt)> {
+    let not_installed = || tr!("limit-server is not installed in {}", &config.install_root);
+
+    let root = fs::canonicalize(&config.install_root).map_err(|_| not_installed().to_string())?;
+    let receipt = Receipt::load(&root)?.ok_or_else(|| not_installed().to_string())?;
+
+    Ok((root, receipt))
+}
+
+// return the absolute path of the command running limit-server
+fn command(config: &InstallConfig, root: &Path, receipt: &Receipt) -> crate::Result<PathBuf> {
+    match &receipt.command {
+        Some(command) => Ok(root.join(command)),
+        None => Err(tr!(
+            "limit-server installed in {} can't run as a service",
+            &config.install_root
+        )
+        .into()),
+    }
+}
+
+// return the user and the group a system service runs limit-server as,
+// which is who ran sudo if limit-up is run with it, rather than Root
+fn service_user(name: Option<&str>) -> crate::Result<(String, String)> {
+    let name = name
+        .map(str::to_string)
+        .or_else(|| env::var("SUDO_USER").ok().filter(|name| !name.is_empty()));
+
+    let user = match &name {
+        Some(name) => User::from_name(name)?,
+        None => User::from_uid(Uid::current())?,
+    }
+    .ok_or_else(|| tr!("User {} is not found", name.unwrap_or_default()).to_string())?;
+
+    let group = Group::from_gid(user.gid)?
+        .map(|group| group.name)
+        .unwrap_or_else(|| user.gid.to_string());
+
+    Ok((user.name, group))
+}
+
+// find how the service installed for the receipt is managed,
+// services of init systems that are not running are ignored
+fn init_system(receipt: &Receipt) -> InitSystem {
+    receipt
+        .services
+        .iter()
+        .find_map(|service| match systemd::scope_of(service) {
+            Some(scope) if systemd::is_running() => Some(InitSystem::Systemd(scope)),
+            None if openrc::is_script(service) && openrc::is_running() => Some(InitSystem::OpenRc),
+            _ => None,
+        })
+        .unwrap_or(InitSystem::Supervisor)
+}
+
+/// Install and enable the service of limit-server installed in the install root
+/// with the running init system, returns the path of the service definition
+pub async fn install(config: &InstallConfig, service: &ServiceConfig) -> crate::Result<PathBuf> {
+    let (root, mut receipt) = installed(config)?;
+    let exec = command(config, &root, &receipt)?;
+    let working_directory = service
+        .working_directory
+        .clone()
+        .unwrap_or_else(|| root.clone());
+
+    let user = match service.scope {
+        Scope::System => Some(service_user(service.user.as_deref())?),
+        Scope::User => None,
+    };
+
+    let path = if systemd::is_running() {
+        let unit = systemd::Unit {
+            scope: service.scope,
+            exec,
+            working_directory,
+            environment: service.environment.clone(),
+            user,
+        };
+
+        let path = systemd::unit_dir(service.scope)?.join(systemd::UNIT_NAME);
+        write_service(config, service.scope, &path, &unit.render(), 0o644).await?;
+        path
+    } else if openrc::is_running() && service.scope == Scope::System {
+        let script = openrc::Script {
+            exec,
+            working_directory,
+            environment: service.environment.clone(),
+            log: supervisor::log_path(&root),
+            user,
+        };
+
+        let path = Path::new(openrc::SCRIPT_DIR).join(openrc::SERVICE_NAME);
+        write_service(config, Scope::System, &path, &script.render(), 0o755).await?;
+        path
+    } else if openrc::is_running() {
+        Err(tr!("OpenRC doesn't support services of users"))?
+    } else {
+        Err(tr!("No supported init system is running, \
+            run `limit-up start` to run limit-server without a service"))?
+    };
+
+    // record the service first so that uninstall removes it even if enabling fails
+    if !receipt.services.contains(&path) {
+        receipt.services.push(path.clone());
+        receipt.save(&root)?;
+    }
+
+    match init_system(&receipt) {
+        InitSystem::Systemd(scope) => {
+            systemd::systemctl(config, scope, &["daemon-reload"]).await?;
+            systemd::systemctl(config, scope, &["enable", systemd::UNIT_NAME]).await?;
+        }
+        InitSystem::OpenRc => openrc::rc_update(config, "add").await?,
+        InitSystem::Supervisor => {}
+    }
+
+    Ok(path)
+}
+
+// write a service definition, system ones are installed as Root
+async fn write_service(
+    config: &InstallConfig,
+    scope: Scope,
+    path: &Path,
+    contents: &str,
+    mode: u32,
+) -> crate::Result<()> {
+    if scope == Scope::User {
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+        fs::set_permissions(path, Permissions::from_mode(mode))?;
+
+        return Ok(());
+    }
+
+    // environment variables may hold secrets, the copy is only readable by us
+    let temp = env::temp_dir().join(format!("limit-up-{}", std::process::id()));
+    write_private(&temp, contents.as_bytes())?;
+
+    let res = run(
+        config,
+        Scope::System,
+        "install",
+        &[
+            "-D",
+            "-m",
+            &format!("{:o}", mode),
+            &temp.display().to_string(),
+            &path.display().to_string(),
+        ],
+    )
+    .await;
+    fs::remove_file(&temp).ok();
+
+    res
+}
+
+/// Disable and remove the service of the scope installed for the install root,
+/// returns the removed service definition if there was one
+pub async fn remove(config: &InstallConfig, scope: Scope) -> crate::Result<Option<PathBuf>> {
+    let Some(mut receipt) = Receipt::load(&config.install_root)? else {
+        return Ok(None);
+    };
+
+    let Some(service) = receipt
+        .services
+        .iter()
+        .find(|service| match systemd::scope_of(service) {
+            Some(unit_scope) => unit_scope == scope,
+            None => openrc::is_script(service) && scope == Scope::System,
+        })
+        .cloned()
+    else {
+        return Ok(None);
+    };
+
+    remove_service(config, &service).await?;
+
+    receipt.services.retain(|s| *s != service);
+    receipt.save(&config.install_root)?;
+
+    Ok(Some(service))
+}
+
+/// Stop, disable and remove a service definition installed by us,
+/// the init system failing to do so is fine since it may not be running anymore
+pub async fn remove_service(config: &InstallConfig, path: &Path) -> crate::Result<()> {
+    let scope = systemd::scope_of(path);
+
+    match scope {
+        Some(scope) => {
+            systemd::systemctl(config, scope, &["disable", "--now", systemd::UNIT_NAME])
+                .await
+                .ok();
+        }
+        None if openrc::is_script(path) => {
+            openrc::rc_service(config, "stop").await.ok();
+            openrc::rc_update(config, "del").await.ok();
+        }
+        None => {}
+    }
+
+    if path.exists() {
+        match scope.unwrap_or(Scope::System) {
+            Scope::System => {
+                run(
+                    config,
+                    Scope::System,
+                    "rm",
+                    &["-f", &path.display().to_string()],
+                )
+                .await?
+            }
+            Scope::User => fs::remove_file(path)?,
+        }
+    }
+
+    if let Some(scope) = scope {
+        systemd::systemctl(config, scope, &["daemon-reload"])
+            .await
+            .ok();
+    }
+
+    Ok(())
+}
+
+/// Start limit-server with the init system its service is installed for,
+/// or with the supervisor of limit-up if there is no service
+pub async fn start(config: &InstallConfig) -> crate::Result<InitSystem> {
+    let (root, receipt) = installed(config)?;
+    let init_system = init_system(&receipt);
+
+    match init_system {
+        InitSystem::Systemd(scope) => {
+            systemd::systemctl(config, scope, &["start", systemd::UNIT_NAME]).await?
+        }
+        InitSystem::OpenRc => openrc::rc_service(config, "start").await?,
+        InitSystem::Supervisor => {
+            command(config, &root, &receipt)?;
+            supervisor::start(&root).await?
+        }
+    }
+
+    Ok(init_system)
+}
+
+/// Stop limit-server, returns whether it was running if that is known
+pub async fn stop(config: &InstallConfig) -> crate::Result<Option<bool>> {
+    let (root, receipt) = installed(config)?;
+
+    match init_system(&receipt) {
+        InitSystem::Systemd(scope) => {
+            systemd::systemctl(config, scope, &["stop", systemd::UNIT_NAME]).await?;
+            Ok(None)
+        }
+        InitSystem::OpenRc => {
+            openrc::rc_service(config, "stop").await?;
+            Ok(None)
+        }
+        InitSystem::Supervisor => Ok(Some(supervisor::stop(&root).await?)),
+    }
+}
+
+/// Restart limit-server, it is started if it is not running
+pub async fn restart(config: &InstallConfig) -> crate::Result<InitSystem> {
+    let (root, receipt) = installed(config)?;
+    let init_system = init_system(&receipt);
+
+    match init_system {
+        InitSystem::Systemd(scope) => {
+            systemd::systemctl(config, scope, &["restart", systemd::UNIT_NAME]).await?
+        }
+        InitSystem::OpenRc => openrc::rc_service(config, "restart").await?,
+        InitSystem::Supervisor => {
+            command(config, &root, &receipt)?;
+            supervisor::stop(&root).await?;
+            supervisor::start(&root).await?
+        }
+    }
+
+    Ok(init_system)
+}
+
+/// Print the last `lines` lines of the log of limit-server, and follow it if `follow`
+pub async fn logs(config: &InstallConfig, lines: usize, follow: bool) -> crate::Result<()> {
+    let (root, receipt) = installed(config)?;
+
+    match init_system(&receipt) {
+        InitSystem::Systemd(scope) => systemd::journal(scope, lines, follow).await,
+        // OpenRC writes the log where the supervisor does
+        InitSystem::OpenRc | InitSystem::Supervisor => {
+            supervisor::tail(&supervisor::log_path(&root), lines, follow).await
+        }
+    }
+}
+
+/// Run limit-server installed in the install root under the supervisor,
+/// this is what `supervisor::start` runs in the background
+pub async fn supervise(config: &InstallConfig) -> crate::Result<()> {
+    let (root, receipt) = installed(config)?;
+
+    supervisor::supervise(&root, &command(config, &root, &receipt)?).await
+}
+
+// run a program of the init system, its stderr is included in the error if it fails,
+// programs managing system services are run as Root
+async fn run(
+    config: &InstallConfig,
+    scope: Scope,
+    program: &str,
+    args: &[&str],
+) -> crate::Result<()> {
+    let output = async {
+        match scope {
+            Scope::System => {
+                let privilege = Privilege::detect(config.elevation.clone())?;
+                let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+                let mut command = privilege.command(program, &args);
+
+                Ok(privilege
+                    .spawn(&mut command)
+                    .await?
+                    .wait_with_output()
+                    .await?)
+            }
+            Scope::User => Ok(Command::new(program)
+                .args(args)
+                .stdin(Stdio::null())
+                .output()
+                .await?),
+        }
+    }
+    .await
+    .map_err(|e: crate::Error| {
+        tr!("Failed to run {}: {}", program.to_string(), e.to_string()).to_string()
+    })?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(tr!(
+            "{} failed ({})\n\n{}",
+            format!("{} {}", program, args.join(" ")),
+            output.status.to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string()
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use super::Scope;
+use crate::core::{helper::is_env_name, installer::InstallConfig};
+
+/// Name of the service of limit-server
+pub const SERVICE_NAME: &str = "limit-server";
+
+/// Directory init scripts are installed into
+pub const SCRIPT_DIR: &str = "/etc/init.d";
+
+/// An init script running limit-server
+#[derive(Debug, Clone)]
+pub struct Script {
+    /// Absolute path of the command running limit-server
+    pub exec: PathBuf,
+    pub working_directory: PathBuf,
+    pub environment: Vec<(String, String)>,
+    /// Where stdout and stderr of limit-server go
+    pub log: PathBuf,
+    /// User and group limit-server runs as, it runs as Root otherwise
+    pub user: Option<(String, String)>,
+}
+
+impl Script {
+    /// Render the init script
+    pub fn render(&self) -> String {
+        let mut environment = String::new();
+        for (key, value) in &self.environment {
+            // keys can't be quoted, they would run as shell code if they were anything else
+            assert!(is_env_name(key), "invalid environment variable {:?}", key);
+            environment += &format!("export {}={}\n", key, quote(value));
+        }
+
+        let user = match &self.user {
+            Some((user, group)) => {
+                format!("command_user={}\n", quote(&format!("{}:{}", user, group)))
+            }
+            None => String::new(),
+        };
+
+        format!(
+            "#!/sbin/openrc-run\n\
+            # Generated by limit-up, changes will be lost when the service is reinstalled\n\
+            \n\
+            description=\"limit-server\"\n\
+            command={}\n\
+            command_background=true\n\
+            {}\
+            pidfile=\"/run/${{RC_SVCNAME}}.pid\"\n\
+            directory={}\n\
+            output_log={}\n\
+            error_log={}\n\
+            {}\
+            \n\
+            depend() {{\n\
+            \tneed net\n\
+            }}\n",
+            quote(&self.exec.display().to_string()),
+            user,
+            quote(&self.working_directory.display().to_string()),
+            quote(&self.log.display().to_string()),
+            quote(&self.log.display().to_string()),
+            environment,
+        )
+    }
+}
+
+// quote a value for sh, nothing is special inside single quotes but single quotes
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Return whether an init script is the one installed by us
+pub fn is_script(path: &Path) -> bool {
+    path == Path::new(SCRIPT_DIR).join(SERVICE_NAME)
+}
+
+/// Return whether OpenRC is the running init system
+pub fn is_running() -> bool {
+    Path::new("/run/openrc/softlevel").exists()
+}
+
+/// Run rc-service for limit-server
+pub async fn rc_service(config: &InstallConfig, action: &str) -> crate::Result<()> {
+    super::run(config, Scope::System, "rc-service", &[SERVICE_NAME, action]).await
+}
+
+/// Add limit-server to or delete it from the default runlevel
+pub async fn rc_update(config: &InstallConfig, action: &str) -> crate::Result<()> {
+    super::run(
+        config,
+        Scope::System,
+        "rc-update",
+        &[action, SERVICE_NAME, "default"],
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Script;
+
+    #[test]
+    fn test_render_script() {
+        let script = Script {
+            exec: PathBuf::from("/opt/limit lab/limit-server.sh"),
+            working_directory: PathBuf::from("/opt/limit lab"),
+            environment: vec![("GREETING".to_string(), "it's".to_string())],
+            log: PathBuf::from("/opt/limit lab/logs/limit-server.log"),
+            user: Some(("limit".to_string(), "lab".to_string())),
+        };
+
+        let rendered = script.render();
+        assert!(rendered.starts_with("#!/sbin/openrc-run\n"));
+        assert!(rendered.contains("\ncommand='/opt/limit lab/limit-server.sh'\n"));
+        assert!(rendered.contains("\ncommand_user='limit:lab'\n"));
+        assert!(rendered.contains("\nexport GREETING='it'\\''s'\n"));
+        assert!(rendered.contains("\n\tneed net\n"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_render_invalid_key() {
+        Script {
+            exec: PathBuf::from("/opt/limit/limit-server.sh"),
+            working_directory: PathBuf::from("/opt/limit"),
+            environment: vec![("X;rm -rf /;Y".to_string(), "1".to_string())],
+            log: PathBuf::from("/opt/limit/logs/limit-server.log"),
+            user: None,
+        }
+        .render();
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use nix::{
+    sys::signal::{kill, killpg, Signal},
+    unistd::Pid,
+};
+use r18::tr;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::Command,
+    select,
+    signal::unix::{signal, SignalKind},
+    time::sleep,
+};
+
+/// Pid file of the supervisor in the install root
+pub const PID_FILE: &str = "limit-server.pid";
+
+/// Directory of logs in the install root
+pub const LOG_DIR: &str = "logs";
+
+/// Log of limit-server in `LOG_DIR`
+pub const LOG_FILE: &str = "limit-server.log";
+
+// a log is rotated once it grows over this size
+const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+
+// number of rotated logs to keep
+const KEEP_LOGS: usize = 5;
+
+// wait this long before restarting limit-server once it failed
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+// wait this long for the supervisor to stop before killing it
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Return the path of the log of limit-server in `root`
+pub fn log_path(root: &Path) -> PathBuf {
+    root.join(LOG_DIR).join(LOG_FILE)
+}
+
+/// Return the pid of the supervisor running for `root`, if there is one
+pub fn running(root: &Path) -> Option<Pid> {
+    let pid = fs::read_to_string(root.join(PID_FILE)).ok()?;
+    let pid = Pid::from_raw(pid.trim().parse().ok()?);
+
+    // the pid file may be left behind if the supervisor was killed
+    kill(pid, None).is_ok().then_some(pid)
+}
+
+/// Start a supervisor of limit-server in the background,
+/// it runs `limit-up supervise` so that it outlives this process
+pub async fn start(root: &Path) -> crate::Result<()> {
+    if let Some(pid) = running(root) {
+        Err(tr!("limit-server is already running (pid {})", pid.to_string()).to_string())?;
+    }
+
+    let mut supervisor = std::process::Command::new(env::current_exe()?)
+        .arg("supervise")
+        .arg("--install-root")
+        .arg(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // its own process group, so that it is not interrupted along with the terminal
+        // and can be killed together with limit-server
+        .process_group(0)
+        .spawn()?;
+
+    // give it a moment to fail
+    sleep(Duration::from_secs(1)).await;
+
+    match supervisor.try_wait()? {
+        None => Ok(()),
+        Some(status) => Err(tr!(
+            "limit-server exited immediately ({}), see {} for details",
+            status.to_string(),
+            log_path(root).display().to_string()
+        )
+        .into()),
+    }
+}
+
+/// Stop the supervisor running for `root` along with limit-server,
+/// returns whether it was running
+pub async fn stop(root: &Path) -> crate::Result<bool> {
+    let Some(pid) = running(root) else {
+        return Ok(false);
+    };
+
+    kill(pid, Signal::SIGTERM)?;
+
+    let mut waited = Duration::ZERO;
+    while kill(pid, None).is_ok() {
+        if waited >= STOP_TIMEOUT {
+            killpg(pid, Signal::SIGKILL)?;
+            fs::remove_file(root.join(PID_FILE)).ok();
+            break;
+        }
+
+        sleep(Duration::from_millis(100)).await;
+        waited += Duration::from_millis(100);
+    }
+
+    Ok(true)
+}
+
+/// Run `command` in `root` until it exits successfully or the supervisor is terminated,
+/// it is restarted if it fails, and its output goes to the rotating log in `root`
+pub async fn supervise(root: &Path, command: &Path) -> crate::Result<()> {
+    let pid_file = root.join(PID_FILE);
+    fs::write(&pid_file, process::id().to_string())?;
+
+    let res = supervise_inner(root, command).await;
+
+    fs::remove_file(&pid_file).ok();
+
+    res
+}
+
+async fn supervise_inner(root: &Path, command: &Path) -> crate::Result<()> {
+    let log = Arc::new(Mutex::new(RotatingLog::open(
+        &log_path(root),
+        MAX_LOG_SIZE,
+        KEEP_LOGS,
+    )?));
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    loop {
+        let mut child = Command::new(command)
+            .current_dir(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = tokio::spawn(copy_lines(child.stdout.take().unwrap(), log.clone()));
+        let stderr = tokio::spawn(copy_lines(child.stderr.take().unwrap(), log.clone()));
+
+        let status = select! {
+            status = child.wait() => status?,
+            _ = terminate.recv() => {
+                if let Some(pid) = child.id() {
+                    kill(Pid::from_raw(pid as i32), Signal::SIGTERM).ok();
+                }
+
+                child.wait().await?;
+                return Ok(());
+            }
+        };
+
+        stdout.await.ok();
+        stderr.await.ok();
+
+        let message = tr!("limit-up: limit-server exited ({})", status.to_string());
+        log.lock()
+            .unwrap()
+            .write(format!("{}\n", message).as_bytes())?;
+
+        if status.success() {
+            return Ok(());
+        }
+
+        select! {
+            _ = sleep(RESTART_DELAY) => {},
+            _ = terminate.recv() => return Ok(()),
+        }
+    }
+}
+
+// copy lines of a stream of the child into the log
+async fn copy_lines(stream: impl AsyncRead + Unpin, log: Arc<Mutex<RotatingLog>>) {
+    let mut stream = BufReader::new(stream);
+    let mut line = Vec::new();
+
+    while stream
+        .read_until(b'\n', &mut line)
+        .await
+        .is_ok_and(|n| n > 0)
+    {
+        if log.lock().unwrap().write(&line).is_err() {
+            break;
+        }
+
+        line.clear();
+    }
+}
+
+/// A log file rotated by size, rotated logs are named `<log>.1` to `<log>.<keep>`,
+/// from the newest to the oldest
+pub struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl RotatingLog {
+    pub fn open(path: &Path, max_size: u64, keep: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: file.metadata()?.len(),
+            file,
+            max_size,
+            keep,
+        })
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + data.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(data)?;
+        self.size += data.len() as u64;
+
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: usize| {
+            let mut path = self.path.as_os_str().to_owned();
+            path.push(format!(".{}", n));
+            PathBuf::from(path)
+        };
+
+        for n in (1..self.keep).rev() {
+            if rotated(n).exists() {
+                fs::rename(rotated(n), rotated(n + 1))?;
+            }
+        }
+
+        match self.keep {
+            0 => fs::remove_file(&self.path)?,
+            _ => fs::rename(&self.path, rotated(1))?,
+        }
+
+        *self = Self::open(&self.path, self.max_size, self.keep)?;
+
+        Ok(())
+    }
+}
+
+/// Print the last `lines` lines of a log, and what is appended to it if `follow`
+pub async fn tail(path: &Path, lines: usize, follow: bool) -> crate::Result<()> {
+    let mut file = File::open(path).map_err(|e| {
+        tr!(
+            "Unable to open {}: {}",
+            path.display().to_string(),
+            e.to_string()
+        )
+        .to_string()
+    })?;
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+
+    // find where the last `lines` lines start, the trailing newline doesn't start a line
+    let body = content.strip_suffix(b"\n").unwrap_or(&content);
+    let start = match lines {
+        0 => content.len(),
+        _ => body
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, b)| **b == b'\n')
+            .nth(lines - 1)
+            .map_or(0, |(i, _)| i + 1),
+    };
+
+    let mut stdout = io::stdout();
+    stdout.write_all(&content[start..])?;
+    stdout.flush()?;
+
+    if !follow {
+        return Ok(());
+    }
+
+    let mut position = content.len() as u64;
+    loop {
+        sleep(Duration::from_millis(500)).await;
+
+        // start over once the log is rotated
+        let len = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        if len < position {
+            file = File::open(path)?;
+            position = 0;
+        }
+
+        file.seek(SeekFrom::Start(position))?;
+        position += io::copy(&mut file, &mut stdout)?;
+        stdout.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix::prelude::PermissionsExt};
+
+    use super::{log_path, supervise, RotatingLog, PID_FILE};
+    use crate::core::RT;
+
+    #[test]
+    fn test_rotating_log() {
+        let dir = env::temp_dir().join("limit-up-log-test");
+        fs::remove_dir_all(&dir).ok();
+
+        let path = dir.join("test.log");
+        let mut log = RotatingLog::open(&path, 8, 2).unwrap();
+        for line in ["1111\n", "2222\n", "3333\n", "4444\n"] {
+            log.write(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "4444\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("test.log.1")).unwrap(),
+            "3333\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("test.log.2")).unwrap(),
+            "2222\n"
+        );
+        assert!(!dir.join("test.log.3").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_supervise() {
+        let dir = env::temp_dir().join("limit-up-supervise-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        let command = dir.join("server.sh");
+        fs::write(&command, "#!/bin/sh\necho out\necho err >&2\n").unwrap();
+        fs::set_permissions(&command, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // the command exits successfully so it is not restarted
+        RT.block_on(supervise(&dir, &command)).unwrap();
+
+        let log = fs::read_to_string(log_path(&dir)).unwrap();
+        assert!(log.contains("out\n"));
+        assert!(log.contains("err\n"));
+        assert!(!dir.join(PID_FILE).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use r18::tr;
+use tokio::process::Command;
+
+use super::Scope;
+use crate::core::installer::InstallConfig;
+
+/// Name of the unit of limit-server
+pub const UNIT_NAME: &str = "limit-server.service";
+
+/// A systemd unit running limit-server
+#[derive(Debug, Clone)]
+pub struct Unit {
+    pub scope: Scope,
+    /// Absolute path of the command running limit-server
+    pub exec: PathBuf,
+    pub working_directory: PathBuf,
+    pub environment: Vec<(String, String)>,
+    /// User and group limit-server runs as, system units run it as Root otherwise
+    pub user: Option<(String, String)>,
+}
+
+impl Unit {
+    /// Render the unit file
+    pub fn render(&self) -> String {
+        let mut environment = String::new();
+        for (key, value) in &self.environment {
+            environment += &format!("Environment={}\n", quote(&format!("{}={}", key, value)));
+        }
+
+        let user = match &self.user {
+            Some((user, group)) => format!("User={}\nGroup={}\n", quote(user), quote(group)),
+            None => String::new(),
+        };
+
+        format!(
+            "# Generated by limit-up, changes will be lost when the service is reinstalled\n\
+            [Unit]\n\
+            Description=limit-server\n\
+            After=network-online.target\n\
+            Wants=network-online.target\n\
+            \n\
+            [Service]\n\
+            Type=simple\n\
+            ExecStart={}\n\
+            WorkingDirectory={}\n\
+            {}\
+            {}\
+            Restart=on-failure\n\
+            RestartSec=5\n\
+            \n\
+            [Install]\n\
+            WantedBy={}\n",
+            quote(&self.exec.display().to_string()).replace('$', "$$"),
+            // paths are taken verbatim, only specifiers need escaping
+            self.working_directory
+                .display()
+                .to_string()
+                .replace('%', "%%"),
+            user,
+            environment,
+            match self.scope {
+                Scope::System => "multi-user.target",
+                Scope::User => "default.target",
+            }
+        )
+    }
+}
+
+// quote a value of a unit file, `%` starts a specifier so it is escaped as well
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%")
+    )
+}
+
+/// Return the directory units of the scope are installed into
+pub fn unit_dir(scope: Scope) -> crate::Result<PathBuf> {
+    match scope {
+        Scope::System => Ok(PathBuf::from("/etc/systemd/system")),
+        Scope::User => env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|config| config.join("systemd").join("user"))
+            .ok_or_else(|| tr!("Unable to find the home directory").into()),
+    }
+}
+
+/// Return the scope of a unit file installed by us
+pub fn scope_of(unit: &Path) -> Option<Scope> {
+    [Scope::System, Scope::User].into_iter().find(|scope| {
+        unit.file_name().is_some_and(|name| name == UNIT_NAME)
+            && unit_dir(*scope).is_ok_and(|dir| unit.parent() == Some(&dir))
+    })
+}
+
+/// Return whether systemd is the running init system
+pub fn is_running() -> bool {
+    Path::new("/run/systemd/system").exists()
+}
+
+// `--user` for user scope
+fn scope_args(scope: Scope) -> &'static [&'static str] {
+    match scope {
+        Scope::System => &[],
+        Scope::User => &["--user"],
+    }
+}
+
+/// Run systemctl in the scope
+pub async fn systemctl(config: &InstallConfig, scope: Scope, args: &[&str]) -> crate::Result<()> {
+    super::run(
+        config,
+        scope,
+        "systemctl",
+        &[scope_args(scope), args].concat(),
+    )
+    .await
+}
+
+/// Print the journal of limit-server
+pub async fn journal(scope: Scope, lines: usize, follow: bool) -> crate::Result<()> {
+    let mut command = Command::new("journalctl");
+    command
+        .args(scope_args(scope))
+        .args(["--unit", UNIT_NAME, "--lines", &lines.to_string()]);
+
+    if follow {
+        command.arg("--follow");
+    }
+
+    let status = command.stdin(Stdio::null()).status().await.map_err(|e| {
+        tr!(
+            "Failed to run {}: {}",
+            "journalctl".to_string(),
+            e.to_string()
+        )
+        .to_string()
+    })?;
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(tr!("journalctl failed ({})", status.to_string()).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Unit;
+    use crate::core::service::Scope;
+
+    #[test]
+    fn test_render_unit() {
+        let mut unit = Unit {
+            scope: Scope::User,
+            exec: PathBuf::from("/opt/limit lab/limit-server.sh"),
+            working_directory: PathBuf::from("/opt/limit lab"),
+            environment: vec![("RUST_LOG".to_string(), "info,a=\"100%\"".to_string())],
+            user: None,
+        };
+
+        let rendered = unit.render();
+        assert!(rendered.contains("\nExecStart=\"/opt/limit lab/limit-server.sh\"\n"));
+        assert!(rendered.contains("\nWorkingDirectory=/opt/limit lab\n"));
+        assert!(rendered.contains("\nEnvironment=\"RUST_LOG=info,a=\\\"100%%\\\"\"\n"));
+        assert!(rendered.contains("\nWantedBy=default.target\n"));
+        assert!(!rendered.contains("\nUser="));
+
+        unit.scope = Scope::System;
+        unit.user = Some(("limit".to_string(), "lab".to_string()));
+
+        let rendered = unit.render();
+        assert!(rendered.contains("\nUser=\"limit\"\nGroup=\"lab\"\n"));
+        assert!(rendered.contains("\nWantedBy=multi-user.target\n"));
+    }
+}
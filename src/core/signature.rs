@@ -0,0 +1,92 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use minisign_verify::{PublicKey, Signature};
+
+/// Minisign public key that limit-server releases are signed with.
+///
+/// This is the public half of the key the limit-server maintainers sign `SHA256SUMS`
+/// and the release assets with. Builds can trust another key instead by setting
+/// `LIMIT_UP_RELEASE_KEY` when building, e.g. for private forks.
+///
+/// To rotate the key, releases are signed with the new key from then on and the key
+/// here is replaced in the next release of limit-up. Older builds of limit-up can
+/// install those releases by passing the new key with `--trusted-key`.
+pub const RELEASE_PUBLIC_KEY: &str = match option_env!("LIMIT_UP_RELEASE_KEY") {
+    Some(key) => key,
+    None => "RWRWnHQ/aa+9tKVXHDTnlosoTgq3Zs+EuNJzNEMfPr2oD3JCkXC1I2Lo",
+};
+
+/// Suffix of the detached signature of a release asset
+pub const SIGNATURE_SUFFIX: &str = ".minisig";
+
+/// Load the trusted public key, which is either a base64 encoded key or
+/// a path to a minisign `.pub` file, the release key is used if no key is given
+pub fn load_public_key(key: Option<&str>) -> crate::Result<PublicKey> {
+    let key = key.unwrap_or(RELEASE_PUBLIC_KEY);
+
+    Ok(match Path::new(key).is_file() {
+        true => PublicKey::decode(&fs::read_to_string(key)?)?,
+        false => PublicKey::from_base64(key.trim())?,
+    })
+}
+
+/// Verify a file against a detached minisign signature
+pub fn verify_file(path: impl AsRef<Path>, signature: &str, key: &PublicKey) -> crate::Result<()> {
+    let signature = Signature::decode(signature)?;
+    let mut verifier = key.verify_stream(&signature)?;
+
+    let mut file = BufReader::new(File::open(path)?);
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        match file.read(&mut buf)? {
+            0 => break,
+            n => verifier.update(&buf[..n]),
+        }
+    }
+
+    Ok(verifier.finalize()?)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{env, fs};
+
+    use super::{load_public_key, verify_file};
+
+    // key pair for tests, `TEST_SIGNATURE` is the signature of "limit"
+    pub const TEST_PUBLIC_KEY: &str = "RWTc6jSvztYWinObzrPpGiV2sVoIvfN/ELsSBSRCjoZym0tEN9hP9MsY";
+    pub const TEST_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUTc6jSvztYWioI88lI3r/FEE56mq7s49wQ9MFa2s420/lAUmqx3IYVqcAZEId491VT8AKf3d60kYCmHJOtbu/Ipm000g887yws=
+trusted comment: timestamp:1700000000\tfile:limit
+cd5EISmwHsraqMZu6IPvGRV3xvK4ppVNvB2ifAtPG6lp7PPGvzHkxrH6CkszWwKOb+eR3RfVUa1mOiv+YrVhAA==
+";
+    // public key of another key pair
+    const OTHER_PUBLIC_KEY: &str = "RWT6SOVh3TDU6smOqNph9fBmbW/M42FSN/jKAgouKixuFdW97CPlx4Su";
+
+    #[test]
+    fn test_verify_file() {
+        let path = env::temp_dir().join("limit-up-signature-test");
+        let key = load_public_key(Some(TEST_PUBLIC_KEY)).unwrap();
+
+        fs::write(&path, "limit").unwrap();
+        assert!(verify_file(&path, TEST_SIGNATURE, &key).is_ok());
+
+        fs::write(&path, "limit?").unwrap();
+        assert!(verify_file(&path, TEST_SIGNATURE, &key).is_err());
+
+        // signed by another key
+        fs::write(&path, "limit").unwrap();
+        let other_key = load_public_key(Some(OTHER_PUBLIC_KEY)).unwrap();
+        assert!(verify_file(&path, TEST_SIGNATURE, &other_key).is_err());
+
+        // the release key is built in
+        assert!(load_public_key(None).is_ok());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use r18::tr;
+use rcgen::{CertificateParams, DnType, KeyPair};
+use x509_parser::pem::Pem;
+
+use super::config::write_private;
+
+/// Directory of generated certificates in the install root
+pub const TLS_DIR: &str = "tls";
+
+/// Generated certificate in `TLS_DIR`
+pub const CERT_FILE: &str = "cert.pem";
+
+/// Generated private key in `TLS_DIR`
+pub const KEY_FILE: &str = "key.pem";
+
+/// Generate a self-signed certificate for `hostname` into `root`,
+/// `names` are IPs and DNS names added as subject alternative names along with `hostname`,
+/// returns paths of the certificate and the private key relative to `root`
+pub fn generate(
+    root: &Path,
+    hostname: &str,
+    names: &[String],
+) -> crate::Result<(PathBuf, PathBuf)> {
+    let mut sans = vec![hostname.to_string()];
+    for name in names {
+        if !sans.contains(name) {
+            sans.push(name.clone());
+        }
+    }
+
+    let mut params = CertificateParams::new(sans)?;
+    params.distinguished_name.push(DnType::CommonName, hostname);
+
+    let key = KeyPair::generate()?;
+    let cert = params.self_signed(&key)?;
+
+    let cert_path = Path::new(TLS_DIR).join(CERT_FILE);
+    let key_path = Path::new(TLS_DIR).join(KEY_FILE);
+
+    fs::create_dir_all(root.join(TLS_DIR))?;
+    write_private(&root.join(&cert_path), cert.pem().as_bytes())?;
+    write_private(&root.join(&key_path), key.serialize_pem().as_bytes())?;
+
+    Ok((cert_path, key_path))
+}
+
+/// Check that the certificate is valid now and the private key belongs to it,
+/// the first certificate of a chain is the one checked
+pub fn check_pair(cert: &Path, key: &Path) -> crate::Result<()> {
+    let read = |path: &Path| {
+        fs::read(path).map_err(|e| {
+            tr!(
+                "Unable to open {}: {}",
+                path.display().to_string(),
+                e.to_string()
+            )
+            .to_string()
+        })
+    };
+
+    let cert_pem = read(cert)?;
+    let key_pem = read(key)?;
+
+    let invalid_cert =
+        || tr!("{} is not a PEM certificate", cert.display().to_string()).to_string();
+    let pem = Pem::iter_from_buffer(&cert_pem)
+        .next()
+        .and_then(|pem| pem.ok())
+        .filter(|pem| pem.label == "CERTIFICATE")
+        .ok_or_else(invalid_cert)?;
+    let x509 = pem.parse_x509().map_err(|_| invalid_cert())?;
+
+    let key_pair = String::from_utf8(key_pem)
+        .ok()
+        .and_then(|pem| KeyPair::from_pem(&pem).ok())
+        .ok_or_else(|| {
+            tr!(
+                "{} is not a PEM private key in PKCS#8",
+                key.display().to_string()
+            )
+            .to_string()
+        })?;
+
+    if !x509.validity().is_valid() {
+        Err(tr!(
+            "{} has expired or is not valid yet",
+            cert.display().to_string()
+        )
+        .to_string())?;
+    }
+
+    if *x509.public_key().subject_public_key.data != *key_pair.public_key_raw() {
+        Err(tr!(
+            "{} doesn't match {}",
+            key.display().to_string(),
+            cert.display().to_string()
+        )
+        .to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix::prelude::PermissionsExt};
+
+    use super::{check_pair, generate};
+
+    #[test]
+    fn test_generate() {
+        let dir = env::temp_dir().join("limit-up-tls-test");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        let names = ["127.0.0.1".to_string(), "limit.local".to_string()];
+        let (cert, key) = generate(&dir, "limit.example.com", &names).unwrap();
+        let (cert, key) = (dir.join(cert), dir.join(key));
+
+        assert_eq!(key.metadata().unwrap().permissions().mode() & 0o777, 0o600);
+        check_pair(&cert, &key).unwrap();
+
+        // a key of another certificate doesn't match
+        let other = dir.join("other");
+        let (_, other_key) = generate(&other, "limit.example.com", &[]).unwrap();
+        assert!(check_pair(&cert, &other.join(other_key)).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
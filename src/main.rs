@@ -1,19 +1,49 @@
 mod_use::mod_use!(args, error, ui);
 
+mod cli;
 mod core;
 
+use std::process::ExitCode;
+
 r18::init!("tr");
 
-fn main() {
+fn main() -> ExitCode {
     let args = Args::parse();
 
     // automatically sets the current locale.
     r18::auto_detect!();
 
-    #[cfg(debug_assertions)]
-    println!("{:#?}", args);
-
     match args.cmd.as_ref().unwrap_or(&Command::Tui) {
-        Command::Tui => Ui::setup().exec(),
-    };
+        Command::Tui => {
+            Ui::setup().exec();
+            ExitCode::SUCCESS
+        }
+        Command::Install(args) => cli::install(args),
+        Command::Update(args) => cli::update(args),
+        Command::Uninstall(args) => cli::uninstall(args),
+        Command::Status(args) => cli::status(args),
+        Command::Doctor(args) => cli::doctor(args),
+        #[cfg(unix)]
+        Command::Service(args) => cli::service(args),
+        #[cfg(unix)]
+        Command::Start(args) => cli::start(args),
+        #[cfg(unix)]
+        Command::Stop(args) => cli::stop(args),
+        #[cfg(unix)]
+        Command::Restart(args) => cli::restart(args),
+        #[cfg(unix)]
+        Command::Logs(args) => cli::logs(args),
+        #[cfg(unix)]
+        Command::Supervise(args) => cli::supervise(args),
+        // services are managed by init systems of unix
+        #[cfg(not(unix))]
+        Command::Service(_)
+        | Command::Start(_)
+        | Command::Stop(_)
+        | Command::Restart(_)
+        | Command::Logs(_)
+        | Command::Supervise(_) => cli::unsupported(),
+        Command::Bundle(args) => cli::bundle(args),
+        Command::Config(args) => cli::config(args),
+    }
 }
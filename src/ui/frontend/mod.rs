@@ -1,3 +1,3 @@
-use cursive::{traits::*, Cursive};
+use cursive::Cursive;
 
-pub fn init_frontend_ui(ui: &mut Cursive) {}
+pub fn init_frontend_ui(_ui: &mut Cursive) {}
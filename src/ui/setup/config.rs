@@ -0,0 +1,354 @@
+use std::{
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use cursive::{
+    theme::BaseColor,
+    traits::*,
+    views::{
+        Button, Checkbox, Dialog, DummyView, EditView, LinearLayout, NamedView, SelectView,
+        TextView,
+    },
+    CbSink, Cursive,
+};
+use r18::tr;
+
+use crate::core::{config::ServerConfig, installer::InstallConfig, tls, RT};
+
+use super::error_dialog;
+
+// width of labels in front of fields
+const LABEL_WIDTH: usize = 18;
+
+// how limit-server gets its certificate
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TlsMode {
+    Disabled,
+    SelfSigned,
+    Existing,
+}
+
+// a labeled field of the config form
+fn field(label: &str, name: &str, content: impl Into<String>, secret: bool) -> LinearLayout {
+    LinearLayout::horizontal()
+        .child(TextView::new(label).fixed_width(LABEL_WIDTH))
+        .child(
+            EditView::new()
+                .content(content)
+                .with(|edit| {
+                    secret.then(|| edit.set_secret(true));
+                })
+                .with_name(name)
+                .full_width(),
+        )
+}
+
+// returns config & deploy page
+pub fn config() -> NamedView<impl View> {
+    let config = ServerConfig::default();
+
+    let form = LinearLayout::vertical()
+        .child(field(
+            tr!("Hostname"),
+            "config_hostname",
+            config.server.hostname,
+            false,
+        ))
+        .child(field(
+            tr!("Listen address"),
+            "config_address",
+            config.server.address,
+            false,
+        ))
+        .child(field(
+            tr!("Port"),
+            "config_port",
+            config.server.port.to_string(),
+            false,
+        ))
+        .child(field(
+            tr!("Data directory"),
+            "config_data_dir",
+            config.server.data_dir.display().to_string(),
+            false,
+        ))
+        .child(field(
+            tr!("Database URL"),
+            "config_database_url",
+            config.database.url,
+            false,
+        ))
+        .child(DummyView {})
+        .child(field(
+            tr!("Admin username"),
+            "config_admin_username",
+            config.admin.username,
+            false,
+        ))
+        .child(field(
+            tr!("Admin password"),
+            "config_admin_password",
+            config.admin.password,
+            true,
+        ))
+        .child(DummyView {})
+        .child(
+            LinearLayout::horizontal()
+                .child(TextView::new(tr!("TLS")).fixed_width(LABEL_WIDTH))
+                .child(
+                    SelectView::new()
+                        .popup()
+                        .item(tr!("Disabled"), TlsMode::Disabled)
+                        .item(
+                            tr!("Generate a self-signed certificate"),
+                            TlsMode::SelfSigned,
+                        )
+                        .item(tr!("Use existing certificate and key"), TlsMode::Existing)
+                        .on_submit(|ui, mode| set_tls_mode(ui, *mode))
+                        .with_name("config_tls"),
+                ),
+        )
+        .child(field(tr!("Certificate"), "config_tls_cert", "", false))
+        .child(field(tr!("Private key"), "config_tls_key", "", false))
+        .child(DummyView {})
+        .child(
+            LinearLayout::horizontal()
+                .child(Checkbox::new().checked().with_name("config_start"))
+                .child(TextView::new(tr!(" Start limit-server after saving"))),
+        )
+        .with(|form| {
+            // certificate files are only asked for existing ones
+            for name in ["config_tls_cert", "config_tls_key"] {
+                form.call_on_name(name, |edit: &mut EditView| edit.disable());
+            }
+        });
+
+    LinearLayout::vertical()
+        .child(TextView::new(tr!("Configure limit-server")).style(BaseColor::Cyan.light()))
+        .child(DummyView {})
+        .child(form.scrollable().full_height())
+        .child(DummyView {})
+        .child(
+            LinearLayout::horizontal()
+                .child(Button::new_raw(tr!("[ Quit ]"), |ui| ui.quit()))
+                .child(DummyView {}.full_width())
+                .child(Button::new_raw(tr!("[ Save ]"), on_save)),
+        )
+        .full_screen()
+        .with_name(tr!("Config & Deploy"))
+}
+
+// enable certificate fields if existing files are used
+fn set_tls_mode(ui: &mut Cursive, mode: TlsMode) {
+    for name in ["config_tls_cert", "config_tls_key"] {
+        ui.find_name::<EditView>(name)
+            .unwrap()
+            .set_enabled(mode == TlsMode::Existing);
+    }
+}
+
+// fill the form with the configuration in the install root,
+// this function will be called once limit-server is installed
+pub fn prepare_config(ui: &mut Cursive) {
+    let root = ui
+        .user_data::<InstallConfig>()
+        .map(|config| config.install_root.clone())
+        .unwrap_or_default();
+
+    // a reinstall keeps the existing configuration
+    let Ok(Some(config)) = ServerConfig::load(root) else {
+        return;
+    };
+
+    let mut set = |name: &str, content: String| {
+        ui.find_name::<EditView>(name).unwrap().set_content(content);
+    };
+
+    set("config_hostname", config.server.hostname);
+    set("config_address", config.server.address);
+    set("config_port", config.server.port.to_string());
+    set(
+        "config_data_dir",
+        config.server.data_dir.display().to_string(),
+    );
+    set("config_database_url", config.database.url);
+    set("config_admin_username", config.admin.username);
+    set("config_admin_password", config.admin.password);
+    set("config_tls_cert", config.tls.cert.display().to_string());
+    set("config_tls_key", config.tls.key.display().to_string());
+
+    // the certificate in use is kept, even if it was generated
+    if config.tls.is_enabled() {
+        ui.find_name::<SelectView<TlsMode>>("config_tls")
+            .unwrap()
+            .set_selection(2);
+        set_tls_mode(ui, TlsMode::Existing);
+    }
+}
+
+// read the form
+fn read_config(ui: &mut Cursive) -> crate::Result<(ServerConfig, TlsMode)> {
+    let content = |ui: &mut Cursive, name: &str| {
+        ui.find_name::<EditView>(name)
+            .unwrap()
+            .get_content()
+            .trim()
+            .to_string()
+    };
+
+    let port = content(ui, "config_port");
+    let tls_mode = ui
+        .find_name::<SelectView<TlsMode>>("config_tls")
+        .unwrap()
+        .selection()
+        .map_or(TlsMode::Disabled, |mode| *mode);
+
+    let mut config = ServerConfig::default();
+    config.server.hostname = content(ui, "config_hostname");
+    config.server.address = content(ui, "config_address");
+    config.server.port = port
+        .parse()
+        .map_err(|_| tr!("Port must be between 1 and 65535").to_string())?;
+    config.server.data_dir = content(ui, "config_data_dir").into();
+    config.database.url = content(ui, "config_database_url");
+    config.admin.username = content(ui, "config_admin_username");
+    // spaces may be part of the password
+    config.admin.password = ui
+        .find_name::<EditView>("config_admin_password")
+        .unwrap()
+        .get_content()
+        .to_string();
+
+    if tls_mode == TlsMode::Existing {
+        config.tls.cert = content(ui, "config_tls_cert").into();
+        config.tls.key = content(ui, "config_tls_key").into();
+
+        if !config.tls.is_enabled() {
+            Err(tr!("A certificate and a private key are required"))?;
+        }
+    }
+
+    config.validate()?;
+
+    Ok((config, tls_mode))
+}
+
+// set up TLS as chosen and write the configuration into the install root
+fn write_config(
+    root: &Path,
+    mut config: ServerConfig,
+    tls_mode: TlsMode,
+) -> crate::Result<PathBuf> {
+    match tls_mode {
+        TlsMode::Disabled => {}
+        TlsMode::SelfSigned => {
+            let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+
+            // clients may reach limit-server by the address it listens on
+            if let Ok(address) = config.server.address.parse::<IpAddr>() {
+                if !address.is_unspecified() {
+                    names.push(address.to_string());
+                }
+            }
+
+            (config.tls.cert, config.tls.key) =
+                tls::generate(root, &config.server.hostname, &names)?;
+        }
+        TlsMode::Existing => {
+            if let Some(problem) = config.file_problems(root).into_iter().next() {
+                Err(problem.message)?;
+            }
+        }
+    }
+
+    config.save(root)
+}
+
+// start limit-server installed in the install root
+#[cfg(unix)]
+async fn start_server(config: &InstallConfig) -> crate::Result<()> {
+    crate::core::service::start(config).await.map(|_| ())
+}
+
+// services are managed by init systems of unix
+#[cfg(not(unix))]
+async fn start_server(_config: &InstallConfig) -> crate::Result<()> {
+    Err(tr!("Unsupported platform").into())
+}
+
+// start limit-server and tell the user how it went after `message`
+async fn start_task(cb_sink: CbSink, config: InstallConfig, message: String) {
+    let res = start_server(&config).await;
+
+    cb_sink
+        .send(Box::new(move |ui| {
+            let status = match res {
+                Ok(_) => tr!("limit-server has been started").to_string(),
+                Err(e) => tr!("Failed to start limit-server: {}", e.to_string()).to_string(),
+            };
+
+            // replace the dialog telling the server is starting
+            ui.pop_layer();
+            ui.add_layer(done_dialog(format!("{}\n\n{}", message, status)));
+        }))
+        .unwrap();
+}
+
+// tell the user the configuration is done
+fn done_dialog(message: String) -> impl View {
+    Dialog::text(message)
+        .title(tr!("Done"))
+        .button(tr!("Ok"), |ui| ui.quit())
+        .max_width(50)
+}
+
+// this function will be called when the user clicks Save button
+fn on_save(ui: &mut Cursive) {
+    let install_config = ui.user_data::<InstallConfig>().cloned().unwrap_or_default();
+    let root = Path::new(&install_config.install_root);
+
+    let res = read_config(ui).and_then(|(config, tls_mode)| write_config(root, config, tls_mode));
+
+    let path = match res {
+        Ok(path) => path,
+        Err(e) => {
+            ui.add_layer(error_dialog(e, false).with(|d| {
+                d.get_inner_mut().add_button(tr!("Ok"), |ui| {
+                    ui.pop_layer();
+                });
+            }));
+
+            return;
+        }
+    };
+
+    let message = tr!(
+        "Configuration has been saved to {}",
+        path.display().to_string()
+    )
+    .to_string();
+
+    if !ui
+        .find_name::<Checkbox>("config_start")
+        .unwrap()
+        .is_checked()
+    {
+        ui.add_layer(done_dialog(message));
+        return;
+    }
+
+    ui.add_layer(
+        Dialog::text(format!(
+            "{}\n\n{}",
+            message,
+            tr!("Starting limit-server...")
+        ))
+        .title(tr!("Done"))
+        .max_width(50),
+    );
+
+    let cb_sink = ui.cb_sink().clone();
+
+    RT.spawn(start_task(cb_sink, install_config, message));
+}
@@ -1,30 +1,31 @@
-use std::env;
-
 use cursive::{
     align::HAlign,
     theme::BaseColor,
     traits::*,
+    utils::markup::StyledString,
     view::ScrollStrategy,
     views::{
-        Button, Dialog, DialogFocus, DummyView, HideableView, LinearLayout, NamedView, PaddedView,
-        Panel, ProgressBar, ResizedView, ScreensView, ScrollView, TextArea, TextView,
+        Button, Dialog, DialogFocus, DummyView, EditView, HideableView, LinearLayout, NamedView,
+        PaddedView, Panel, ProgressBar, ResizedView, ScreensView, ScrollView, SelectView, TextArea,
+        TextView,
     },
     CbSink, Cursive,
 };
 
 use r18::tr;
+use tokio::{join, sync::mpsc::UnboundedReceiver};
 
 use crate::{
     core::{
-        installer::{self, InstallConfig},
+        installer::{self, Elevation, Events, InstallConfig, InstallEvent, Stream},
+        release::{Channel, Version},
         RT,
     },
     ui::widgets::StepTabs,
-    Result,
 };
 
 // convenient function to create an error dialog
-fn error_dialog(message: impl ToString, default_button: bool) -> ResizedView<Dialog> {
+pub fn error_dialog(message: impl ToString, default_button: bool) -> ResizedView<Dialog> {
     Dialog::text(tr!("Error: {}", message.to_string()))
         .title(tr!("Oops"))
         .with(|d| {
@@ -78,17 +79,6 @@ pub fn install() -> NamedView<impl View> {
 // initialize configure ui
 // this function will be called when the user clicks Next button on the welcome page
 pub fn prepare_install(ui: &mut Cursive) {
-    // PackageManager for FreeBSD requires Root permission
-    #[cfg(target_os = "freebsd")]
-    if !nix::unistd::Uid::effective().is_root() {
-        ui.add_layer(error_dialog(
-            tr!("Permission denied, please rerun as Root"),
-            true,
-        ));
-
-        return;
-    }
-
     let screens = ScreensView::new().with(|screens| {
         screens.add_screen(config_dialog());
         screens.add_screen(cancel_dialog());
@@ -96,6 +86,30 @@ pub fn prepare_install(ui: &mut Cursive) {
 
     ui.set_user_data(InstallConfig::default());
     ui.add_layer(screens.with_name("install_screens"));
+
+    RT.spawn(list_versions(ui.cb_sink().clone()));
+}
+
+// add available releases to the version selector,
+// only channels can be chosen if they are unavailable
+async fn list_versions(cb_sink: CbSink) {
+    for mirror in InstallConfig::default().mirrors {
+        if let Ok(releases) = mirror.releases().await {
+            cb_sink
+                .send(Box::new(move |ui| {
+                    if let Some(mut select) = ui.find_name::<SelectView<Version>>("install_version")
+                    {
+                        for release in releases.into_iter().filter(|r| !r.draft) {
+                            select
+                                .add_item(release.tag_name.clone(), Version::Tag(release.tag_name));
+                        }
+                    }
+                }))
+                .unwrap();
+
+            break;
+        }
+    }
 }
 
 // configure automatic installation
@@ -109,10 +123,7 @@ fn config_dialog() -> Dialog {
             .child(DummyView {})
             .child(
                 TextArea::new()
-                    .content(format!(
-                        "{}/.limit-lab",
-                        env::var("HOME").unwrap_or_default()
-                    ))
+                    .content(InstallConfig::default().install_root)
                     .with_name("install_root")
                     .min_size((30, 2))
                     .max_size((50, 2))
@@ -120,6 +131,33 @@ fn config_dialog() -> Dialog {
                     .title(tr!("Install root"))
                     .title_position(HAlign::Left),
             )
+            .child(
+                EditView::new()
+                    .secret()
+                    .with_name("root_password")
+                    .wrap_with(Panel::new)
+                    .title(tr!("Root Password (if any)"))
+                    .title_position(HAlign::Left)
+                    .wrap_with(|panel| HideableView::new(panel).visible(asks_password())),
+            )
+            .child(DummyView {})
+            .child(
+                LinearLayout::horizontal()
+                    .child(TextView::new(tr!("Version: ")))
+                    .child(
+                        SelectView::<Version>::new()
+                            .popup()
+                            .with_all(
+                                [Channel::Stable, Channel::Prerelease, Channel::Nightly].map(
+                                    |channel| {
+                                        let version = Version::Latest(channel);
+                                        (version.to_string(), version)
+                                    },
+                                ),
+                            )
+                            .with_name("install_version"),
+                    ),
+            )
             .child(DummyView {})
             .child(
                 TextView::new(tr!(
@@ -139,6 +177,36 @@ fn config_dialog() -> Dialog {
     })
 }
 
+// whether commands that need Root are run with sudo, which reads the password from the dialog
+fn asks_password() -> bool {
+    #[cfg(unix)]
+    return installer::privilege::Privilege::detect(Elevation::Terminal)
+        .is_ok_and(|privilege| privilege.tool() == Some("sudo"));
+
+    #[cfg(not(unix))]
+    false
+}
+
+// commands that need Root can't prompt on the terminal the TUI is drawn on,
+// only sudo can be given the password, doas and pkexec would fail or prompt there
+fn check_elevation() -> crate::Result<()> {
+    #[cfg(unix)]
+    if let Some(tool) = installer::privilege::Privilege::detect(Elevation::Terminal)
+        .ok()
+        .and_then(|privilege| privilege.tool())
+        .filter(|tool| *tool != "sudo")
+    {
+        return Err(tr!(
+            "{} can't ask for the password here, please rerun limit-up as Root\n\n{}",
+            tool.to_string(),
+            crate::core::helper::Help::Root.to_string()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
 // help information about manual install
 // this dialog will appear when the user doesn't want install automatically
 fn cancel_dialog() -> Dialog {
@@ -156,19 +224,59 @@ fn cancel_dialog() -> Dialog {
         .title(tr!("Installation Cancelled"))
 }
 
+// unhide install page and set the tip
+pub fn show_install_page(ui: &mut Cursive, tip: &str) {
+    ui.find_name::<HideableView<ResizedView<PaddedView<LinearLayout>>>>(tr!("Install"))
+        .unwrap()
+        .unhide();
+
+    ui.find_name::<TextView>("install_tip")
+        .unwrap()
+        .set_content(tip);
+}
+
 // this function will be called when the user confirms automatic installation
 fn on_install(ui: &mut Cursive) {
+    if let Err(e) = check_elevation() {
+        ui.add_layer(error_dialog(e, false).with(|d| {
+            d.get_inner_mut().add_button(tr!("Ok"), |ui| {
+                ui.pop_layer();
+            });
+        }));
+
+        return;
+    }
+
     ui.user_data::<InstallConfig>().unwrap().install_root = ui
         .find_name::<TextArea>("install_root")
         .unwrap()
         .get_content()
         .into();
 
-    ui.pop_layer();
+    ui.user_data::<InstallConfig>().unwrap().version = ui
+        .find_name::<SelectView<Version>>("install_version")
+        .unwrap()
+        .selection()
+        .map(|version| (*version).clone())
+        .unwrap_or_default();
 
-    ui.find_name::<HideableView<ResizedView<PaddedView<LinearLayout>>>>(tr!("Install"))
+    // the user has agreed to install dependencies
+    ui.user_data::<InstallConfig>()
         .unwrap()
-        .unhide();
+        .install_dependencies = true;
+
+    // commands can't prompt for passwords on the terminal the TUI is drawn on
+    let password = ui
+        .find_name::<EditView>("root_password")
+        .unwrap()
+        .get_content()
+        .to_string();
+    ui.user_data::<InstallConfig>().unwrap().elevation =
+        Elevation::Password((!password.is_empty()).then(|| password.into()));
+
+    ui.pop_layer();
+
+    show_install_page(ui, tr!("Installing..."));
 
     let config = ui.take_user_data::<InstallConfig>().unwrap();
     let cb_sink = ui.cb_sink().clone();
@@ -176,100 +284,78 @@ fn on_install(ui: &mut Cursive) {
     RT.spawn(install_task(cb_sink, config));
 }
 
-// install limit backend
+// install limit backend,
+// advance to the config page on success
 async fn install_task(cb_sink: CbSink, config: InstallConfig) {
-    if let Err(e) = install_task_inner(&cb_sink, config).await {
-        cb_sink
-            .send(Box::new(|ui| {
-                ui.add_layer(error_dialog(e, true));
-            }))
-            .unwrap();
-    }
+    let (events, receiver) = Events::channel();
+    let (res, _) = join!(
+        installer::install(config.clone(), events),
+        show_events(cb_sink.clone(), receiver)
+    );
 
-    // finished
     cb_sink
-        .send(Box::new(|ui| {
-            ui.find_name::<StepTabs>("step_tabs").unwrap().next();
+        .send(Box::new(move |ui| match res {
+            Ok(_) => {
+                ui.set_user_data(config);
+                super::prepare_config(ui);
+                ui.find_name::<StepTabs>("step_tabs").unwrap().next();
+            }
+            Err(e) => {
+                ui.add_layer(error_dialog(e, true));
+            }
         }))
         .unwrap();
 }
 
-// linux implementation
-#[cfg(target_os = "linux")]
-async fn install_task_inner(cb_sink: &CbSink, config: InstallConfig) -> Result<()> {
-    cb_sink
-        .send(Box::new(move |ui| {
-            ui.find_name::<TextView>("install_tip")
-                .unwrap()
-                .set_content(tr!("Downloading limit-server..."));
-        }))
-        .unwrap();
-
-    let cb_sink = cb_sink.clone();
-    let callback = move |progress| {
+// show events of the installer on install page until it is done
+pub async fn show_events(cb_sink: CbSink, mut receiver: UnboundedReceiver<InstallEvent>) {
+    while let Some(event) = receiver.recv().await {
         cb_sink
-            .send(Box::new(move |ui| {
-                ui.find_name::<ProgressBar>("install_progress")
-                    .unwrap()
-                    .set_value(progress);
-            }))
+            .send(Box::new(move |ui| show_event(ui, event)))
             .unwrap();
-    };
-
-    installer::install(config, callback).await
+    }
 }
 
-// freebsd implementation
-#[cfg(target_os = "freebsd")]
-async fn install_task_inner(cb_sink: &CbSink, config: InstallConfig) -> Result<()> {
-    use cursive::utils::markup::StyledString;
-
-    cb_sink
-        .send(Box::new(move |ui| {
+// update the tip, the progress bar and details on install page,
+// details are shown automatically once something goes wrong
+fn show_event(ui: &mut Cursive, event: InstallEvent) {
+    let (line, failed) = match event {
+        InstallEvent::Progress { current, total } => {
+            ui.find_name::<ProgressBar>("install_progress")
+                .unwrap()
+                .set_value(current * 100 / total.max(1));
+            return;
+        }
+        InstallEvent::StepStarted(step) => {
+            // steps may list what they run on the following lines
             ui.find_name::<TextView>("install_tip")
                 .unwrap()
-                .set_content(tr!("Installing Elixir..."));
-        }))
-        .unwrap();
+                .set_content(step.lines().next().unwrap_or_default());
+            (step, false)
+        }
+        InstallEvent::Log { stream, line } => (line, stream == Stream::Stderr),
+        InstallEvent::Warning(message) => (tr!("Warning: {}", message).to_string(), true),
+        InstallEvent::Error(message) => (tr!("Error: {}", message).to_string(), true),
+        InstallEvent::StepFinished(_) => return,
+    };
 
-    let cb_sink = cb_sink.clone();
-    installer::install(config, move |progress, out, err| {
-        cb_sink
-            .send(Box::new(move |ui| {
-                if !out.is_empty() || !err.is_empty() {
-                    let new_line = match out.is_empty() {
-                        true => StyledString::from(out),
-                        false => StyledString::styled(err, BaseColor::Red.light()),
-                    };
-
-                    ui.find_name::<HideableView<ResizedView<Panel<ScrollView<TextView>>>>>(
-                        "install_detail",
-                    )
-                    .unwrap()
-                    .get_inner_mut()
-                    .get_inner_mut()
-                    .get_inner_mut()
-                    .get_inner_mut()
-                    .append(new_line);
-                }
-
-                ui.find_name::<ProgressBar>("install_progress")
-                    .unwrap()
-                    .set_value(progress);
-            }))
-            .unwrap();
-    })
-    .await
-}
+    let mut detail = ui
+        .find_name::<HideableView<ResizedView<Panel<ScrollView<TextView>>>>>("install_detail")
+        .unwrap();
 
-// windows implementation
-#[cfg(target_os = "windows")]
-async fn install_task_inner(cb_sink: &CbSink, config: InstallConfig) -> Result<()> {
-    installer::install(config, move |_p| {}).await
-}
+    let line = format!("{}\n", line);
+    let new_line = match failed {
+        false => StyledString::from(line),
+        true => {
+            detail.unhide();
+            StyledString::styled(line, BaseColor::Red.light())
+        }
+    };
 
-// dummy implementation
-#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "windows")))]
-async fn install_task_inner(cb_sink: &CbSink, config: InstallConfig) -> Result<()> {
-    Err(tr!("Unsupported platform").into())
+    detail
+        .get_inner_mut()
+        .get_inner_mut()
+        .get_inner_mut()
+        .get_inner_mut()
+        .append(new_line);
 }
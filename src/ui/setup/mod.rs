@@ -1,6 +1,6 @@
-mod_use::mod_use!(welcome, install);
+mod_use::mod_use!(welcome, install, config, update, uninstall);
 
-use cursive::{traits::*, views::DummyView, Cursive};
+use cursive::{traits::*, Cursive};
 
 use super::widgets::StepTabs;
 
@@ -9,8 +9,7 @@ pub fn init_setup_ui(ui: &mut Cursive) {
     let tab = StepTabs::new()
         .with_tab(welcome())
         .with_tab(install())
-        // TODO
-        .with_tab(DummyView {}.with_name("Config & Deploy"))
+        .with_tab(config())
         .with_name("step_tabs");
 
     ui.add_fullscreen_layer(tab);
@@ -0,0 +1,142 @@
+use cursive::{
+    align::HAlign,
+    theme::BaseColor,
+    traits::*,
+    views::{Checkbox, Dialog, DummyView, LinearLayout, Panel, TextArea, TextView},
+    CbSink, Cursive,
+};
+
+use r18::tr;
+use tokio::join;
+
+use crate::{
+    core::{
+        installer::{self, Action, Elevation, Events, InstallConfig},
+        RT,
+    },
+    ui::widgets::StepTabs,
+};
+
+// initialize uninstall ui
+// this function will be called when the user clicks Uninstall button on the welcome page
+pub fn prepare_uninstall(ui: &mut Cursive) {
+    ui.add_layer(uninstall_dialog());
+}
+
+// ask the user where limit-server was installed
+fn uninstall_dialog() -> Dialog {
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(
+                TextArea::new()
+                    .content(InstallConfig::default().install_root)
+                    .with_name("uninstall_root")
+                    .min_size((30, 2))
+                    .max_size((50, 2))
+                    .wrap_with(Panel::new)
+                    .title(tr!("Install root"))
+                    .title_position(HAlign::Left),
+            )
+            .child(DummyView {})
+            .child(
+                LinearLayout::horizontal()
+                    .child(Checkbox::new().with_name("uninstall_purge"))
+                    .child(TextView::new(tr!(" Remove user data and configuration"))),
+            )
+            .fixed_width(40),
+    )
+    .title(tr!("Uninstall limit-server"))
+    .button(tr!("Cancel"), |ui| {
+        ui.pop_layer();
+    })
+    .button(tr!("Next"), on_uninstall)
+}
+
+// this function will be called when the user confirms the install root
+fn on_uninstall(ui: &mut Cursive) {
+    let config = InstallConfig {
+        install_root: ui
+            .find_name::<TextArea>("uninstall_root")
+            .unwrap()
+            .get_content()
+            .into(),
+        // commands can't prompt for passwords on the terminal the TUI is drawn on
+        elevation: Elevation::Password(None),
+        ..Default::default()
+    };
+    let purge = ui
+        .find_name::<Checkbox>("uninstall_purge")
+        .unwrap()
+        .is_checked();
+
+    match installer::uninstall_plan(&config, purge) {
+        Ok(plan) if plan.is_empty() => ui.add_layer(
+            Dialog::info(tr!("limit-server is not installed, nothing to remove")).max_width(50),
+        ),
+        Ok(plan) => ui.add_layer(plan_dialog(config, plan)),
+        Err(e) => ui.add_layer(super::error_dialog(e, false).with(|d| {
+            d.get_inner_mut().add_button(tr!("Ok"), |ui| {
+                ui.pop_layer();
+            });
+        })),
+    }
+}
+
+// show the user what is going to be removed
+fn plan_dialog(config: InstallConfig, plan: Vec<Action>) -> Dialog {
+    let actions = plan
+        .iter()
+        .map(|action| action.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(tr!("The following actions will be taken:")))
+            .child(DummyView {})
+            .child(
+                TextView::new(actions)
+                    .style(BaseColor::Yellow.light())
+                    .scrollable()
+                    .max_height(10),
+            )
+            .fixed_width(50),
+    )
+    .title(tr!("Uninstall limit-server"))
+    .button(tr!("Previous"), |ui| {
+        ui.pop_layer();
+    })
+    .button(tr!("Remove"), move |ui| {
+        // pop plan dialog and uninstall dialog
+        ui.pop_layer();
+        ui.pop_layer();
+
+        ui.find_name::<StepTabs>("step_tabs").unwrap().next();
+        super::show_install_page(ui, tr!("Uninstalling limit-server..."));
+
+        let cb_sink = ui.cb_sink().clone();
+
+        RT.spawn(uninstall_task(cb_sink, config.clone(), plan.clone()));
+    })
+}
+
+// remove limit backend
+async fn uninstall_task(cb_sink: CbSink, config: InstallConfig, plan: Vec<Action>) {
+    let (events, receiver) = Events::channel();
+    let (res, _) = join!(
+        installer::uninstall(config, plan, events),
+        super::show_events(cb_sink.clone(), receiver)
+    );
+
+    cb_sink
+        .send(Box::new(move |ui| match res {
+            Ok(_) => ui.add_layer(
+                Dialog::text(tr!("limit-server has been uninstalled"))
+                    .title(tr!("Uninstall limit-server"))
+                    .button(tr!("Ok"), |ui| ui.quit())
+                    .max_width(50),
+            ),
+            Err(e) => ui.add_layer(super::error_dialog(e, true)),
+        }))
+        .unwrap();
+}
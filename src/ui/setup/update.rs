@@ -0,0 +1,101 @@
+use cursive::{
+    align::HAlign,
+    traits::*,
+    views::{Dialog, LinearLayout, Panel, TextArea},
+    CbSink, Cursive,
+};
+
+use r18::tr;
+use tokio::join;
+
+use crate::{
+    core::{
+        installer::{self, Events, InstallConfig, UpdateResult},
+        RT,
+    },
+    ui::widgets::StepTabs,
+};
+
+// initialize update ui
+// this function will be called when the user clicks Update button on the welcome page
+pub fn prepare_update(ui: &mut Cursive) {
+    ui.add_layer(update_dialog());
+}
+
+// ask the user where limit-server was installed
+fn update_dialog() -> Dialog {
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(
+                TextArea::new()
+                    .content(InstallConfig::default().install_root)
+                    .with_name("update_root")
+                    .min_size((30, 2))
+                    .max_size((50, 2))
+                    .wrap_with(Panel::new)
+                    .title(tr!("Install root"))
+                    .title_position(HAlign::Left),
+            )
+            .fixed_width(40),
+    )
+    .title(tr!("Update limit-server"))
+    .button(tr!("Cancel"), |ui| {
+        ui.pop_layer();
+    })
+    .button(tr!("Update"), on_update)
+}
+
+// this function will be called when the user confirms the update
+fn on_update(ui: &mut Cursive) {
+    let config = InstallConfig {
+        install_root: ui
+            .find_name::<TextArea>("update_root")
+            .unwrap()
+            .get_content()
+            .into(),
+        ..Default::default()
+    };
+
+    ui.pop_layer();
+
+    ui.find_name::<StepTabs>("step_tabs").unwrap().next();
+    super::show_install_page(ui, tr!("Updating limit-server..."));
+
+    let cb_sink = ui.cb_sink().clone();
+
+    RT.spawn(update_task(cb_sink, config));
+}
+
+// update limit backend
+async fn update_task(cb_sink: CbSink, config: InstallConfig) {
+    let (events, receiver) = Events::channel();
+    let (res, _) = join!(
+        installer::update(config, events),
+        super::show_events(cb_sink.clone(), receiver)
+    );
+
+    cb_sink
+        .send(Box::new(move |ui| {
+            let message = match res {
+                Ok(UpdateResult::UpToDate(version)) => {
+                    tr!("limit-server is up to date ({})", version).to_string()
+                }
+                Ok(UpdateResult::Updated { from, to }) => {
+                    let from = from.unwrap_or_else(|| tr!("unknown version").to_string());
+                    tr!("limit-server has been updated from {} to {}", from, to).to_string()
+                }
+                Err(e) => {
+                    ui.add_layer(super::error_dialog(e, true));
+                    return;
+                }
+            };
+
+            ui.add_layer(
+                Dialog::text(message)
+                    .title(tr!("Update limit-server"))
+                    .button(tr!("Ok"), |ui| ui.quit())
+                    .max_width(50),
+            );
+        }))
+        .unwrap();
+}
@@ -21,13 +21,20 @@ A CLI tool that helps you to setup limit-server :)"));
             LinearLayout::horizontal()
                 .child(Button::new_raw(tr!("[ Quit ]"), |ui| ui.quit()))
                 .child(DummyView {}.full_width())
+                .child(Button::new_raw(
+                    tr!("[ Uninstall ]"),
+                    super::prepare_uninstall,
+                ))
+                .child(DummyView {})
+                .child(Button::new_raw(tr!("[ Update ]"), super::prepare_update))
+                .child(DummyView {})
                 .child(Button::new_raw(tr!("[ Next ]"), |ui| {
                     ui.find_name::<StepTabs>("step_tabs").unwrap().next();
                     super::prepare_install(ui);
                 }))
                 .with(|layout| {
                     // move focus to the Next button
-                    layout.set_focus_index(2).unwrap();
+                    layout.set_focus_index(6).unwrap();
                 }),
         )
         .with_name(tr!("Welcome"))